@@ -0,0 +1,174 @@
+use quicli::prelude::*;
+use std::path::PathBuf;
+
+use crate::config::Configuration;
+use crate::stats;
+
+/// Ties the population size and the number of generations to the `DIMENSION`
+/// of each instance, so that larger instances get a larger budget.
+pub struct Scaling {
+    pub pop_per_node: Option<f64>,
+    pub gen_per_node: Option<f64>,
+}
+
+impl Scaling {
+    pub fn apply(&self, configuration: &Configuration, dimension: usize) -> Configuration {
+        let scale = |factor: f64| ((factor * dimension as f64).ceil() as usize).max(1);
+        Configuration {
+            pop_size: self.pop_per_node.map_or(configuration.pop_size, scale),
+            generations: self.gen_per_node.map_or(configuration.generations, scale),
+            ..configuration.clone()
+        }
+    }
+}
+
+pub struct Row {
+    pub instance: String,
+    pub dimension: usize,
+    pub configuration: Configuration,
+    pub best: u64,
+    pub avg: f64,
+    pub std: f64,
+    pub optimum: Option<u64>,
+}
+
+impl Row {
+    /// Relative gap of `value` to the known optimum, in percent.
+    pub fn gap(&self, value: f64) -> Option<f64> {
+        self.optimum.map(|optimum| 100.0 * (value - optimum as f64) / optimum as f64)
+    }
+}
+
+/// Expands a directory into the `.tsp` files it contains, and anything else
+/// into the files matching it as a glob pattern.
+pub fn instance_paths(pattern: &str) -> Result<Vec<PathBuf>, Error> {
+    let directory = PathBuf::from(pattern);
+    let pattern = if directory.is_dir() {
+        directory.join("*.tsp").to_string_lossy().into_owned()
+    } else {
+        pattern.to_owned()
+    };
+    let mut paths = glob::glob(&pattern)?.collect::<Result<Vec<PathBuf>, glob::GlobError>>()?;
+    ensure!(!paths.is_empty(), "no files match pattern {:?}", pattern);
+    paths.sort();
+    Ok(paths)
+}
+
+pub fn benchmark(configuration: &Configuration, instance_paths: &[PathBuf], runs: usize, seed: u64,
+    scaling: &Scaling) -> Result<Vec<Row>, Error>
+{
+    use assg1::tsp::{optima::known_optimum, parser::parse_problem_instance};
+
+    let mut rows = Vec::new();
+    for instance_path in instance_paths {
+        let problem = parse_problem_instance(instance_path)?;
+        let instance = instance_path.file_stem().unwrap().to_string_lossy().into_owned();
+        let configuration = scaling.apply(configuration, problem.dimension());
+        info!("running {} on {}", configuration, instance);
+
+        let bests: Vec<f64> = configuration.bests(&problem, runs, seed)?.iter()
+            .map(|best| *best as f64)
+            .collect();
+        rows.push(Row {
+            optimum: known_optimum(&instance),
+            instance: instance,
+            dimension: problem.dimension(),
+            configuration: configuration,
+            best: bests.iter().cloned().fold(f64::INFINITY, f64::min) as u64,
+            avg: stats::mean(&bests),
+            std: stats::std(&bests),
+        });
+    }
+    Ok(rows)
+}
+
+pub fn print_table(rows: &[Row]) {
+    fn or_dash(value: Option<String>) -> String {
+        value.unwrap_or_else(|| "-".to_owned())
+    }
+
+    println!("% instance & dimension & pop & gen & best & avg & std & optimum & best gap & avg gap");
+    for row in rows {
+        println!("{} & {} & {} & {} & {} & {:.2} & {:.2} & {} & {} & {} \\\\",
+            row.instance, row.dimension, row.configuration.pop_size, row.configuration.generations,
+            row.best, row.avg, row.std,
+            or_dash(row.optimum.map(|optimum| optimum.to_string())),
+            or_dash(row.gap(row.best as f64).map(|gap| format!("{:.2}\\%", gap))),
+            or_dash(row.gap(row.avg).map(|gap| format!("{:.2}\\%", gap))));
+    }
+}
+
+/// Dumps the same columns as `print_table`, with the whole configuration in
+/// place of the population size and the number of generations, and empty
+/// fields where the optimum is unknown.
+pub fn dump_table(rows: &[Row], path: &PathBuf) -> Result<(), Error> {
+    let header = "instance;dimension;configuration;best;avg;std;optimum;best gap;avg gap".to_owned();
+    let output = std::iter::once(header)
+        .chain(rows.iter()
+            .map(|row| format!("{};{};{};{};{};{};{};{};{}",
+                row.instance, row.dimension, row.configuration, row.best, row.avg, row.std,
+                row.optimum.map(|optimum| optimum.to_string()).unwrap_or_default(),
+                row.gap(row.best as f64).map(|gap| gap.to_string()).unwrap_or_default(),
+                row.gap(row.avg).map(|gap| gap.to_string()).unwrap_or_default())))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    write_to_file(path, &output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaling() {
+        let configuration = "pop=100,gen=50".parse::<Configuration>().unwrap();
+        let scaling = Scaling { pop_per_node: Some(0.5), gen_per_node: None };
+        let scaled = scaling.apply(&configuration, 52);
+        assert_eq!(scaled.pop_size, 26);
+        assert_eq!(scaled.generations, 50);
+
+        let scaling = Scaling { pop_per_node: None, gen_per_node: Some(0.001) };
+        assert_eq!(scaling.apply(&configuration, 52).generations, 1);
+    }
+
+    #[test]
+    fn test_benchmark() {
+        let configuration = "init=greedy:1.0,pop=10,gen=5".parse::<Configuration>().unwrap();
+        let paths = vec![PathBuf::from("data/berlin52.tsp"), PathBuf::from("data/berlin11_modified.tsp")];
+        let scaling = Scaling { pop_per_node: None, gen_per_node: None };
+        let rows = benchmark(&configuration, &paths, 3, 0, &scaling).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].instance, "berlin52");
+        assert_eq!(rows[0].dimension, 52);
+        assert_eq!(rows[0].optimum, Some(7542));
+        assert!(rows[0].best >= 7542);
+        assert!(rows[0].best as f64 <= rows[0].avg);
+        assert!(rows[0].std >= 0.0);
+        let gap = rows[0].gap(rows[0].best as f64).unwrap();
+        assert!((gap - 100.0 * (rows[0].best - 7542) as f64 / 7542.0).abs() < 1e-9);
+
+        assert_eq!(rows[1].optimum, None);
+        assert_eq!(rows[1].gap(rows[1].avg), None);
+
+        let path = std::env::temp_dir().join("assg1-bench.csv");
+        dump_table(&rows, &path).unwrap();
+        let table = read_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let columns = lines[0].split(';').count();
+        assert!(lines.iter().all(|line| line.split(';').count() == columns));
+        assert!(lines[2].ends_with(";;;"));
+    }
+
+    #[test]
+    fn test_instance_paths() {
+        let paths = instance_paths("data").unwrap();
+        assert!(paths.contains(&PathBuf::from("data/berlin52.tsp")));
+        assert!(paths.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(instance_paths("data/kroA1*.tsp").unwrap().len(), 2);
+        assert!(instance_paths("data/*.nothing").is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use quicli::prelude::*;
+use std::path::PathBuf;
+
+use crate::config::Configuration;
+use crate::stats;
+
+/// The best fitness of a single run of a configuration on an instance.
+pub struct Record {
+    pub instance: String,
+    pub configuration: String,
+    pub best: u64,
+}
+
+pub fn run_records(configurations: &[Configuration], instance_paths: &[PathBuf], runs: usize, seed: u64)
+    -> Result<Vec<Record>, Error>
+{
+    use assg1::tsp::parser::parse_problem_instance;
+
+    let mut records = Vec::new();
+    for instance_path in instance_paths {
+        let problem = parse_problem_instance(instance_path)?;
+        // names inside the files are not unique, e.g. berlin11_modified is called berlin52
+        let instance = instance_path.file_stem().unwrap().to_string_lossy().into_owned();
+        for configuration in configurations {
+            info!("running {} on {}", configuration, instance);
+            // every configuration sees the same seeds
+            for best in configuration.bests(&problem, runs, seed)? {
+                records.push(Record {
+                    instance: instance.clone(),
+                    configuration: configuration.to_string(),
+                    best: best,
+                });
+            }
+        }
+    }
+    Ok(records)
+}
+
+pub fn dump_records(records: &[Record], path: &PathBuf) -> Result<(), Error> {
+    let output = records.iter()
+        .map(|record| format!("{};{};{}", record.instance, record.configuration, record.best))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    write_to_file(path, &output)
+}
+
+pub fn load_records(path: &PathBuf) -> Result<Vec<Record>, Error> {
+    read_file(path)?.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            let values: Vec<&str> = line.split(';').collect();
+            ensure!(values.len() == 3, "expected 3 fields on line {} of {:?}", i + 1, path);
+            Ok(Record {
+                instance: values[0].to_owned(),
+                configuration: values[1].to_owned(),
+                best: values[2].trim().parse::<u64>()?,
+            })
+        })
+        .collect()
+}
+
+fn unique<'a>(values: impl Iterator<Item=&'a String>) -> Vec<&'a String> {
+    let mut unique: Vec<&String> = Vec::new();
+    for value in values {
+        if !unique.contains(&value) {
+            unique.push(value);
+        }
+    }
+    return unique;
+}
+
+/// Prints the summary of every configuration on every instance, the pairwise
+/// rank-sum tests with their effect sizes, and the Friedman ranking across the
+/// instances on which all configurations were run.
+pub fn print_summary(records: &[Record]) {
+    let instances = unique(records.iter().map(|record| &record.instance));
+    let configurations = unique(records.iter().map(|record| &record.configuration));
+    let bests = |instance: &String, configuration: &String| -> Vec<f64> {
+        records.iter()
+            .filter(|record| &record.instance == instance && &record.configuration == configuration)
+            .map(|record| record.best as f64)
+            .collect()
+    };
+
+    for (i, configuration) in configurations.iter().enumerate() {
+        println!("% C{} = {}", i + 1, configuration);
+    }
+
+    println!("% instance & configuration & best & avg & std & runs");
+    for instance in &instances {
+        for (i, configuration) in configurations.iter().enumerate() {
+            let values = bests(instance, configuration);
+            if values.is_empty() {
+                continue;
+            }
+            let best = values.iter().cloned().fold(f64::INFINITY, f64::min);
+            println!("{} & C{} & {} & {:.2} & {:.2} & {} \\\\",
+                instance, i + 1, best, stats::mean(&values), stats::std(&values), values.len());
+        }
+    }
+
+    println!("% instance & comparison & U & z & p & A12");
+    for instance in &instances {
+        for i in 0..configurations.len() {
+            for j in i + 1..configurations.len() {
+                let a = bests(instance, configurations[i]);
+                let b = bests(instance, configurations[j]);
+                if a.is_empty() || b.is_empty() {
+                    continue;
+                }
+                let test = stats::mann_whitney(&a, &b);
+                println!("{} & C{} vs C{} & {} & {:.3} & {:.4}{} & {:.3} \\\\",
+                    instance, i + 1, j + 1, test.u, test.z, test.p, if test.p < 0.05 { "*" } else { "" },
+                    stats::vargha_delaney(&a, &b));
+            }
+        }
+    }
+
+    let blocks: Vec<Vec<f64>> = instances.iter()
+        .map(|instance| configurations.iter()
+            .map(|configuration| bests(instance, configuration))
+            .filter(|values| !values.is_empty())
+            .map(|values| stats::mean(&values))
+            .collect::<Vec<f64>>())
+        .filter(|block| block.len() == configurations.len())
+        .collect();
+    if configurations.len() > 1 && !blocks.is_empty() {
+        let test = stats::friedman(&blocks);
+        println!("% configuration & average rank over {} instances", blocks.len());
+        for (i, rank) in test.average_ranks.iter().enumerate() {
+            println!("C{} & {:.2} \\\\", i + 1, rank);
+        }
+        println!("% Friedman chi^2 = {:.4}, p = {:.4}", test.chi_squared, test.p);
+    }
+}
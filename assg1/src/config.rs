@@ -0,0 +1,361 @@
+use quicli::prelude::*;
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::str::FromStr;
+
+use assg1::ea::{self, Evolutionary, Individual};
+use assg1::problem::Permutation;
+use assg1::tsp::{TSP, Distance, ops};
+use assg1::tsp::construct::Heuristic;
+use rand::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Initialization {
+    Random,
+    Greedy(f64),
+    /// Nearest neighbor tours started from distinct nodes.
+    DistinctGreedy(f64),
+    LocalSearch(f64),
+    Construction(Heuristic, f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Selection {
+    Tournament(usize),
+    RouletteWheel(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Crossover {
+    OX(f64),
+    CX(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Mutation {
+    Swap(f64),
+    Inversion(f64),
+}
+
+/// The operators and hyperparameters of the evolutionary algorithm, chosen at
+/// runtime instead of in the source code.
+///
+/// A configuration is written as comma-separated `key=value` pairs, where the
+/// parameter of an operator follows a colon, e.g.
+/// `init=greedy:0.1,select=tournament:15,crossover=ox:0.8,mutate=inversion:0.1,pop=1000,gen=250`.
+/// Omitted keys keep their default values. `improve` is the probability of
+/// improving a mutated individual with local search, and is only written when
+/// it is not zero.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Configuration {
+    pub initialization: Initialization,
+    pub selection: Selection,
+    pub crossover: Crossover,
+    pub mutation: Mutation,
+    #[serde(default)]
+    pub improvement: f64,
+    pub pop_size: usize,
+    pub generations: usize,
+}
+
+impl Default for Configuration {
+    fn default() -> Configuration {
+        Configuration {
+            initialization: Initialization::Random,
+            selection: Selection::Tournament(15),
+            crossover: Crossover::OX(0.8),
+            mutation: Mutation::Inversion(0.1),
+            improvement: 0.0,
+            pop_size: 1000,
+            generations: 250,
+        }
+    }
+}
+
+impl fmt::Display for Configuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.initialization {
+            Initialization::Random => write!(f, "init=random")?,
+            Initialization::Greedy(percentage) => write!(f, "init=greedy:{}", percentage)?,
+            Initialization::DistinctGreedy(percentage) => write!(f, "init=greedy-distinct:{}", percentage)?,
+            Initialization::LocalSearch(percentage) => write!(f, "init=opt:{}", percentage)?,
+            Initialization::Construction(heuristic, percentage) => write!(f, "init={}:{}", heuristic, percentage)?,
+        }
+        match self.selection {
+            Selection::Tournament(tour_size) => write!(f, ",select=tournament:{}", tour_size)?,
+            Selection::RouletteWheel(beta) => write!(f, ",select=roulette:{}", beta)?,
+        }
+        match self.crossover {
+            Crossover::OX(probability) => write!(f, ",crossover=ox:{}", probability)?,
+            Crossover::CX(probability) => write!(f, ",crossover=cx:{}", probability)?,
+        }
+        match self.mutation {
+            Mutation::Swap(probability) => write!(f, ",mutate=swap:{}", probability)?,
+            Mutation::Inversion(probability) => write!(f, ",mutate=inversion:{}", probability)?,
+        }
+        if self.improvement > 0.0 {
+            write!(f, ",improve={}", self.improvement)?;
+        }
+        write!(f, ",pop={},gen={}", self.pop_size, self.generations)
+    }
+}
+
+impl FromStr for Configuration {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Configuration, Error> {
+        fn parameter<T: FromStr>(operator: &str, parameter: Option<&str>) -> Result<T, Error> {
+            parameter
+                .ok_or_else(|| format_err!("missing parameter of {:?}", operator))?
+                .parse::<T>()
+                .map_err(|_| format_err!("invalid parameter of {:?}", operator))
+        }
+
+        let mut configuration = Configuration::default();
+        for pair in source.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let equals_index = pair.find('=')
+                .ok_or_else(|| format_err!("expected a key=value pair, got {:?}", pair))?;
+            let (key, value) = (&pair[..equals_index], &pair[equals_index + 1..]);
+            let mut parts = value.splitn(2, ':');
+            let operator = parts.next().unwrap();
+            let argument = parts.next();
+            match key {
+                "init" => configuration.initialization = match operator {
+                    "random" => Initialization::Random,
+                    "greedy" => Initialization::Greedy(parameter(operator, argument)?),
+                    "greedy-distinct" => Initialization::DistinctGreedy(parameter(operator, argument)?),
+                    "opt" => Initialization::LocalSearch(parameter(operator, argument)?),
+                    _ => match operator.parse::<Heuristic>() {
+                        Ok(heuristic) => Initialization::Construction(heuristic, parameter(operator, argument)?),
+                        Err(_) => return Err(format_err!("unknown initialization {:?}", operator))
+                    }
+                },
+                "select" => configuration.selection = match operator {
+                    "tournament" => Selection::Tournament(parameter(operator, argument)?),
+                    "roulette" => Selection::RouletteWheel(parameter(operator, argument)?),
+                    _ => return Err(format_err!("unknown selection {:?}", operator))
+                },
+                "crossover" => configuration.crossover = match operator {
+                    "ox" => Crossover::OX(parameter(operator, argument)?),
+                    "cx" => Crossover::CX(parameter(operator, argument)?),
+                    _ => return Err(format_err!("unknown crossover {:?}", operator))
+                },
+                "mutate" => configuration.mutation = match operator {
+                    "swap" => Mutation::Swap(parameter(operator, argument)?),
+                    "inversion" => Mutation::Inversion(parameter(operator, argument)?),
+                    _ => return Err(format_err!("unknown mutation {:?}", operator))
+                },
+                "improve" => configuration.improvement = parameter(key, Some(value))?,
+                "pop" => configuration.pop_size = parameter(key, Some(value))?,
+                "gen" => configuration.generations = parameter(key, Some(value))?,
+                _ => return Err(format_err!("unknown key {:?}", key))
+            }
+        }
+        ensure!(configuration.selection != Selection::Tournament(0), "the tournament size must be at least 1");
+        ensure!(configuration.pop_size > 0, "the population size must be at least 1");
+        ensure!(configuration.generations > 0, "the number of generations must be at least 1");
+        Ok(configuration)
+    }
+}
+
+pub enum AnyInitialize<'a, L: Distance = u64> {
+    Random(ops::initialize::Random<'a, TSP<L>>),
+    Greedy(ops::initialize::Greedy<'a, L>),
+    LocalSearch(ops::initialize::LocalSearch<'a, L>),
+    Construction(ops::initialize::Construction<'a, L>),
+}
+
+impl<L: Distance> ea::Initialize for AnyInitialize<'_, L> {
+    type Problem = TSP<L>;
+
+    fn initialize<R: Rng>(&self, pop_size: usize, rng: &mut R) -> Vec<Individual<TSP<L>>> {
+        match self {
+            Self::Random(operator) => operator.initialize(pop_size, rng),
+            Self::Greedy(operator) => operator.initialize(pop_size, rng),
+            Self::LocalSearch(operator) => operator.initialize(pop_size, rng),
+            Self::Construction(operator) => operator.initialize(pop_size, rng),
+        }
+    }
+}
+
+pub enum AnySelect<P: Permutation = TSP> {
+    Tournament(ops::select::Tournament<P>),
+    RouletteWheel(ops::select::RouletteWheel<P>),
+}
+
+impl<P: Permutation> ea::Select for AnySelect<P> {
+    type Problem = P;
+
+    fn select<'a, R: Rng>(&self, population: &'a Vec<Individual<P>>, rng: &mut R) -> &'a Individual<P> {
+        match self {
+            Self::Tournament(operator) => operator.select(population, rng),
+            Self::RouletteWheel(operator) => operator.select(population, rng),
+        }
+    }
+}
+
+pub enum AnyCrossover<'a, P: Permutation = TSP> {
+    OX(ops::crossover::OX<'a, P>),
+    CX(ops::crossover::CX<'a, P>),
+}
+
+impl<P: Permutation> ea::Crossover for AnyCrossover<'_, P> {
+    type Problem = P;
+
+    fn crossover<'a, R: Rng>(&self, a: &'a Individual<P>, b: &'a Individual<P>, rng: &mut R)
+        -> Individual<P>
+    {
+        match self {
+            Self::OX(operator) => operator.crossover(a, b, rng),
+            Self::CX(operator) => operator.crossover(a, b, rng),
+        }
+    }
+}
+
+pub enum AnyMutate<'a, P: Permutation = TSP> {
+    Swap(ops::mutate::Swap<'a, P>),
+    Inversion(ops::mutate::Inversion<'a, P>),
+}
+
+impl<P: Permutation> ea::Mutate for AnyMutate<'_, P> {
+    type Problem = P;
+
+    fn mutate<R: Rng>(&self, individual: &mut Individual<P>, rng: &mut R) {
+        match self {
+            Self::Swap(operator) => operator.mutate(individual, rng),
+            Self::Inversion(operator) => operator.mutate(individual, rng),
+        }
+    }
+}
+
+pub type ConfiguredEvolutionary<'a, L = u64> = Evolutionary<'a, TSP<L>, AnyInitialize<'a, L>, AnySelect<TSP<L>>,
+    AnyCrossover<'a, TSP<L>>, ops::mutate::Improve<'a, AnyMutate<'a, TSP<L>>, L>>;
+
+/// The evolutionary algorithm on a permutation problem other than the TSP,
+/// which only the random initialization applies to.
+pub type PermutationEvolutionary<'a, P> =
+    Evolutionary<'a, P, ops::initialize::Random<'a, P>, AnySelect<P>, AnyCrossover<'a, P>, AnyMutate<'a, P>>;
+
+impl Configuration {
+    pub fn build<'a, L: Distance>(&self, problem: &'a TSP<L>) -> ConfiguredEvolutionary<'a, L> {
+        Evolutionary::new(
+            match self.initialization {
+                Initialization::Random => AnyInitialize::Random(ops::initialize::Random::new(problem)),
+                Initialization::Greedy(percentage) =>
+                    AnyInitialize::Greedy(ops::initialize::Greedy::new(problem, percentage)),
+                Initialization::DistinctGreedy(percentage) =>
+                    AnyInitialize::Greedy(ops::initialize::Greedy::new(problem, percentage).distinct_starts(true)),
+                Initialization::LocalSearch(percentage) =>
+                    AnyInitialize::LocalSearch(ops::initialize::LocalSearch::new(problem, percentage)),
+                Initialization::Construction(heuristic, percentage) =>
+                    AnyInitialize::Construction(ops::initialize::Construction::new(problem, heuristic, percentage)),
+            },
+            self.select(),
+            self.crossover(problem),
+            ops::mutate::Improve::new(problem, self.mutate(problem), self.improvement),
+            self.pop_size,
+            self.generations,
+        ).validate(problem)
+    }
+
+    /// Builds the evolutionary algorithm for any permutation problem, failing
+    /// if the configuration relies on the distances between TSP nodes.
+    pub fn build_permutation<'a, P: Permutation>(&self, problem: &'a P) -> Result<PermutationEvolutionary<'a, P>, Error> {
+        ensure!(self.initialization == Initialization::Random,
+            "initialization {:?} is only available for the TSP", self.initialization);
+        ensure!(self.improvement == 0.0, "improvement is only available for the TSP");
+        Ok(Evolutionary::new(
+            ops::initialize::Random::new(problem),
+            self.select(),
+            self.crossover(problem),
+            self.mutate(problem),
+            self.pop_size,
+            self.generations,
+        ).validate(problem))
+    }
+
+    fn select<P: Permutation>(&self) -> AnySelect<P> {
+        match self.selection {
+            Selection::Tournament(tour_size) => AnySelect::Tournament(ops::select::Tournament::new(tour_size)),
+            Selection::RouletteWheel(beta) => AnySelect::RouletteWheel(ops::select::RouletteWheel::new(beta)),
+        }
+    }
+
+    fn crossover<'a, P: Permutation>(&self, problem: &'a P) -> AnyCrossover<'a, P> {
+        match self.crossover {
+            Crossover::OX(probability) => AnyCrossover::OX(ops::crossover::OX::new(problem, probability)),
+            Crossover::CX(probability) => AnyCrossover::CX(ops::crossover::CX::new(problem, probability)),
+        }
+    }
+
+    fn mutate<'a, P: Permutation>(&self, problem: &'a P) -> AnyMutate<'a, P> {
+        match self.mutation {
+            Mutation::Swap(probability) => AnyMutate::Swap(ops::mutate::Swap::new(problem, probability)),
+            Mutation::Inversion(probability) => AnyMutate::Inversion(ops::mutate::Inversion::new(problem, probability)),
+        }
+    }
+
+    /// Runs the configuration `runs` times with consecutive seeds and returns
+    /// the best fitness of each run.
+    pub fn bests(&self, problem: &TSP, runs: usize, seed: u64) -> Result<Vec<u64>, Error> {
+        use assg1::tsp::logs::Discoverer;
+
+        let mut discoverer = Discoverer::new();
+        for run in 0..runs {
+            self.build(problem)
+                .seed(seed.wrapping_add(run as u64))
+                .run(&mut vec![&mut discoverer])?;
+            discoverer.carry();
+        }
+        Ok(discoverer.bests().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_configuration() {
+        assert_eq!(
+            "select=roulette:0.5, mutate=swap:0.01,gen=10".parse::<Configuration>().unwrap(),
+            Configuration {
+                selection: Selection::RouletteWheel(0.5),
+                mutation: Mutation::Swap(0.01),
+                generations: 10,
+                ..Configuration::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_construction() {
+        assert_eq!(
+            "init=christofides:0.05".parse::<Configuration>().unwrap().initialization,
+            Initialization::Construction(Heuristic::Christofides, 0.05)
+        );
+    }
+
+    #[test]
+    fn test_configuration_round_trip() {
+        let configuration = Configuration {
+            initialization: Initialization::LocalSearch(0.25),
+            crossover: Crossover::CX(0.7),
+            improvement: 0.05,
+            pop_size: 100,
+            ..Configuration::default()
+        };
+        assert_eq!(configuration.to_string().parse::<Configuration>().unwrap(), configuration);
+    }
+
+    #[test]
+    fn test_parse_configuration_error() {
+        assert!("select=tournament".parse::<Configuration>().is_err());
+        assert!("crossover=pmx:0.5".parse::<Configuration>().is_err());
+        assert!("init=savings".parse::<Configuration>().is_err());
+        assert!("pop".parse::<Configuration>().is_err());
+        assert!("select=tournament:0".parse::<Configuration>().is_err());
+        assert!("pop=0".parse::<Configuration>().is_err());
+        assert!("gen=0".parse::<Configuration>().is_err());
+    }
+}
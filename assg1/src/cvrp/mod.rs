@@ -0,0 +1,107 @@
+pub mod parser;
+
+use crate::tsp::dm::DistanceMatrix;
+
+/// Capacitated vehicle routing problem with a single depot.
+///
+/// A solution is a giant tour, i.e. a permutation of the indices of the
+/// customers, which `split` optimally cuts into routes that respect the
+/// capacity of the vehicles. This lets the permutation operators of the TSP
+/// work on it unchanged.
+pub struct CVRP {
+    #[allow(dead_code)]
+    pub name: Option<String>,
+    capacity: u32,
+    depot: usize,
+    demands: Vec<u32>,
+    /// The nodes other than the depot, in the order of the node coord section.
+    customers: Vec<usize>,
+    dm: DistanceMatrix,
+}
+
+impl CVRP {
+    fn distance(&self, a: usize, b: usize) -> u64 {
+        *self.dm.get(a, b).unwrap() as u64
+    }
+
+    /// Cuts the giant tour into the routes of the lowest total length, using
+    /// the shortest path formulation of Prins. Returns the total length and the
+    /// routes as lists of nodes, without the depot.
+    pub fn split(&self, giant_tour: &[usize]) -> (u64, Vec<Vec<usize>>) {
+        let nodes: Vec<usize> = giant_tour.iter().map(|customer| self.customers[*customer]).collect();
+        let size = nodes.len();
+        // the lowest length of the routes serving the first i customers, and
+        // where the last of these routes starts
+        let mut lengths = vec![u64::MAX; size + 1];
+        let mut starts = vec![0; size + 1];
+        lengths[0] = 0;
+        for i in 0..size {
+            if lengths[i] == u64::MAX {
+                continue;
+            }
+            let mut load = 0;
+            let mut length = 0;
+            for j in i..size {
+                load += self.demands[nodes[j]];
+                if load > self.capacity {
+                    break;
+                }
+                length = if j == i {
+                    self.distance(self.depot, nodes[j]) + self.distance(nodes[j], self.depot)
+                } else {
+                    length - self.distance(nodes[j - 1], self.depot)
+                        + self.distance(nodes[j - 1], nodes[j]) + self.distance(nodes[j], self.depot)
+                };
+                if lengths[i] + length < lengths[j + 1] {
+                    lengths[j + 1] = lengths[i] + length;
+                    starts[j + 1] = i;
+                }
+            }
+        }
+
+        let mut routes = Vec::new();
+        let mut end = size;
+        while end > 0 {
+            routes.push(nodes[starts[end]..end].to_vec());
+            end = starts[end];
+        }
+        routes.reverse();
+        return (lengths[size], routes);
+    }
+}
+
+use crate::problem::{Problem, Permutation, validate_permutation};
+use quicli::prelude::Error;
+
+impl Problem for CVRP {
+    type Solution = Vec<usize>;
+    type Measure = u64;
+
+    fn fitness(&self, solution: &Vec<usize>) -> u64 {
+        let (length, _) = self.split(solution);
+        length
+    }
+    fn validate(&self, solution: &Vec<usize>) -> Result<(), Error> {
+        Ok(validate_permutation(solution, self.customers.len())?)
+    }
+}
+
+impl Permutation for CVRP {
+    type Length = u64;
+
+    fn size(&self) -> usize {
+        self.customers.len()
+    }
+}
+
+/// Formats the routes in the CVRPLIB solution format, numbering the nodes as
+/// in the instance file.
+pub fn format_solution(routes: &[Vec<usize>], cost: u64) -> String {
+    let mut output = String::new();
+    for (i, route) in routes.iter().enumerate() {
+        let nodes: Vec<String> = route.iter().map(|node| (node + 1).to_string()).collect();
+        output.push_str(&format!("Route #{}: {}\n", i + 1, nodes.join(" ")));
+    }
+    output.push_str(&format!("Cost {}\n", cost));
+    return output;
+}
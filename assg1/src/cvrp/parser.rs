@@ -0,0 +1,128 @@
+use quicli::prelude::*;
+
+use super::CVRP;
+use crate::tsp::CoordinateSystem;
+use crate::tsp::parser::distance_matrix;
+use std::path::PathBuf;
+
+enum Section {
+    Specification,
+    NodeCoord,
+    Demand,
+    Depot,
+}
+
+/// Parses a TSPLIB/CVRPLIB file with a `CAPACITY`, a `DEMAND_SECTION` and a
+/// `DEPOT_SECTION` holding a single depot.
+pub fn parse_problem_instance(vrp_path: &PathBuf) -> Result<CVRP, Error> {
+    let mut name: Option<String> = None;
+    let mut dimension: Option<usize> = None;
+    let mut capacity: Option<u32> = None;
+    let mut coord_system: Option<CoordinateSystem> = None;
+    let mut coordinates: Vec<(f64, f64)> = Vec::new();
+    let mut demands: Vec<(usize, u32)> = Vec::new();
+    let mut depots: Vec<usize> = Vec::new();
+
+    let file = read_file(vrp_path)?;
+    let mut section = Section::Specification;
+    for line in file.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        match line {
+            "NODE_COORD_SECTION" => section = Section::NodeCoord,
+            "DEMAND_SECTION" => section = Section::Demand,
+            "DEPOT_SECTION" => section = Section::Depot,
+            "EOF" => break,
+            _ => match section {
+                Section::Specification => if let Some(semicolon_index) = line.find(':') {
+                    let value = line[semicolon_index + 1..].trim();
+                    match line[..semicolon_index].trim() {
+                        "NAME" => name = Some(value.to_owned()),
+                        "DIMENSION" => dimension = Some(value.parse::<usize>()?),
+                        "CAPACITY" => capacity = Some(value.parse::<u32>()?),
+                        "EDGE_WEIGHT_TYPE" => coord_system = match value {
+                            "EUC_2D" => Some(CoordinateSystem::Euclidean),
+                            "GEO" => Some(CoordinateSystem::Geographical),
+                            _ => return Err(format_err!("unknown edge weight type"))
+                        },
+                        _ => {}
+                    }
+                },
+                Section::NodeCoord => {
+                    let values: Vec<&str> = line.split_whitespace().collect();
+                    ensure!(values.len() == 3, "invalid node coord line {:?}", line);
+                    coordinates.push((values[1].parse::<f64>()?, values[2].parse::<f64>()?));
+                },
+                Section::Demand => {
+                    let values: Vec<&str> = line.split_whitespace().collect();
+                    ensure!(values.len() == 2, "invalid demand line {:?}", line);
+                    demands.push((values[0].parse::<usize>()?, values[1].parse::<u32>()?));
+                },
+                Section::Depot => match line.parse::<i64>()? {
+                    -1 => section = Section::Specification,
+                    depot => depots.push(depot as usize),
+                }
+            }
+        }
+    }
+
+    let coord_system = coord_system.ok_or_else(|| format_err!("edge weight type missing"))?;
+    let capacity = capacity.ok_or_else(|| format_err!("capacity missing"))?;
+    let dimension = dimension.unwrap_or(coordinates.len());
+    ensure!(coordinates.len() == dimension,
+        "expected {} nodes in the node coord section, got {}", dimension, coordinates.len());
+    ensure!(depots.len() == 1, "expected a single depot, got {}", depots.len());
+    let depot = depots[0].checked_sub(1).filter(|depot| *depot < dimension)
+        .ok_or_else(|| format_err!("invalid depot {}", depots[0]))?;
+
+    let mut node_demands = vec![0; dimension];
+    for (node, demand) in demands {
+        ensure!(node >= 1 && node <= dimension, "invalid node {} in the demand section", node);
+        ensure!(demand <= capacity, "demand {} of node {} exceeds the capacity", demand, node);
+        node_demands[node - 1] = demand;
+    }
+    info!("parsed {:?} with {} nodes and capacity {}", name, dimension, capacity);
+
+    Ok(CVRP {
+        name: name,
+        capacity: capacity,
+        depot: depot,
+        demands: node_demands,
+        customers: (0..dimension).filter(|node| *node != depot).collect(),
+        dm: distance_matrix(&coord_system, &coordinates, 0)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::Problem;
+
+    fn parse(name: &str, source: &str) -> Result<CVRP, Error> {
+        let path = std::env::temp_dir().join(format!("assg1-{}.vrp", name));
+        write_to_file(&path, source).unwrap();
+        let problem = parse_problem_instance(&path);
+        std::fs::remove_file(&path).unwrap();
+        problem
+    }
+
+    const LINE: &str = "NAME : line\nTYPE : CVRP\nDIMENSION : 5\nEDGE_WEIGHT_TYPE : EUC_2D\nCAPACITY : 2\n\
+        NODE_COORD_SECTION\n 1 0 0\n 2 10 0\n 3 20 0\n 4 0 10\n 5 0 20\n\
+        DEMAND_SECTION\n1 0\n2 1\n3 1\n4 1\n5 1\nDEPOT_SECTION\n 1\n -1\nEOF\n";
+
+    #[test]
+    fn test_split() {
+        let problem = parse("line", LINE).unwrap();
+        assert_eq!(problem.capacity, 2);
+        assert_eq!(problem.customers, vec![1, 2, 3, 4]);
+        // out and back along each axis, two customers per vehicle
+        assert_eq!(problem.split(&[0, 1, 2, 3]), (80, vec![vec![1, 2], vec![3, 4]]));
+        assert_eq!(problem.fitness(&vec![1, 0, 2, 3]), 80);
+        assert_eq!(problem.split(&[0, 2, 1, 3]), (102, vec![vec![1, 3], vec![2, 4]]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("no-capacity", &LINE.replace("CAPACITY : 2\n", "")).is_err());
+        assert!(parse("no-depot", &LINE.replace(" 1\n -1\n", " -1\n")).is_err());
+        assert!(parse("excess", &LINE.replace("3 1\n", "3 7\n")).is_err());
+    }
+}
@@ -1,5 +1,13 @@
-use crate::problem::Problem;
+use crate::problem::{Problem, debug_validate};
+use rand::Rng;
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use quicli::prelude::*;
 
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "P::Solution: Serialize, P::Measure: Serialize",
+    deserialize = "P::Solution: DeserializeOwned, P::Measure: DeserializeOwned"
+))]
 pub struct Individual<P: Problem> {
     pub genotype: P::Solution,
     pub fitness: P::Measure,
@@ -17,30 +25,54 @@ impl<P: Problem> Clone for Individual<P> {
 pub trait Initialize {
     type Problem: Problem;
 
-    fn initialize(&self, pop_size: usize) -> Vec<Individual<Self::Problem>>;
+    fn initialize<R: Rng>(&self, pop_size: usize, rng: &mut R) -> Vec<Individual<Self::Problem>>;
 }
 
 pub trait Select {
     type Problem: Problem;
 
-    fn select<'a>(&self, population: &'a Vec<Individual<Self::Problem>>)
+    fn select<'a, R: Rng>(&self, population: &'a Vec<Individual<Self::Problem>>, rng: &mut R)
         -> &'a Individual<Self::Problem>;
 }
 
 pub trait Crossover {
     type Problem: Problem;
 
-    fn crossover<'a>(&self, a: &'a Individual<Self::Problem>, b: &'a Individual<Self::Problem>)
-        -> Individual<Self::Problem>;
+    fn crossover<'a, R: Rng>(&self, a: &'a Individual<Self::Problem>, b: &'a Individual<Self::Problem>,
+        rng: &mut R) -> Individual<Self::Problem>;
 }
 
 pub trait Mutate {
     type Problem: Problem;
 
-    fn mutate(&self, individual: &mut Individual<Self::Problem>);
+    fn mutate<R: Rng>(&self, individual: &mut Individual<Self::Problem>, rng: &mut R);
 }
 
-pub struct Evolutionary<P: Problem, I, S, C, M>
+/// The state of an interrupted run, saved at the end of a generation.
+///
+/// The random number generator is stored as its seed and the position in its
+/// keystream, which is enough to reproduce every subsequent draw.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "P::Solution: Serialize, P::Measure: Serialize",
+    deserialize = "P::Solution: DeserializeOwned, P::Measure: DeserializeOwned"
+))]
+struct Checkpoint<P: Problem> {
+    generation: usize,
+    population: Vec<Individual<P>>,
+    seed: u64,
+    word_pos: u128,
+    loggers: Vec<serde_json::Value>,
+}
+
+use std::path::PathBuf;
+
+/// Where and how often the checkpoints are saved, and the function saving
+/// them, which only exists for problems whose solutions and measures can be
+/// serialized.
+type Checkpointing<P> = (PathBuf, usize, fn(&PathBuf, &Checkpoint<P>) -> Result<(), Error>);
+
+pub struct Evolutionary<'a, P: Problem, I, S, C, M>
 where
     I: Initialize<Problem=P>,
     S: Select<Problem=P>,
@@ -53,19 +85,25 @@ where
     mutate: M,
     pop_size: usize,
     generations: usize,
+    seed: Option<u64>,
+    checkpoint: Option<Checkpointing<P>>,
+    validator: Option<&'a P>,
 }
 
 use crate::log::Log;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 
-impl<P: Problem, I, S, C, M> Evolutionary<P, I, S, C, M>
+impl<'a, P: Problem, I, S, C, M> Evolutionary<'a, P, I, S, C, M>
 where
     I: Initialize<Problem=P>,
     S: Select<Problem=P>,
     C: Crossover<Problem=P>,
-    M: Mutate<Problem=P>
+    M: Mutate<Problem=P>,
+    P::Measure: PartialEq
 {
-    pub fn new<'a>(initialize: I, select: S, crossover: C, mutate: M,
-        pop_size: usize, generations: usize) -> Evolutionary<P, I, S, C, M>
+    pub fn new(initialize: I, select: S, crossover: C, mutate: M,
+        pop_size: usize, generations: usize) -> Evolutionary<'a, P, I, S, C, M>
     {
         Evolutionary {
             initialize: initialize,
@@ -74,27 +112,53 @@ where
             mutate: mutate,
             pop_size: pop_size,
             generations: generations,
+            seed: None,
+            checkpoint: None,
+            validator: None,
         }
     }
 
-    pub fn run(&self, loggers: &mut Vec<&mut dyn Log<(P::Solution, P::Measure)>>) {
-        use quicli::prelude::*;
+    /// Seeds the random number generator, making the run reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Checks every individual against the problem in debug builds, panicking
+    /// if its solution is infeasible or its fitness is stale.
+    pub fn validate(mut self, problem: &'a P) -> Self {
+        self.validator = Some(problem);
+        self
+    }
+
+    pub fn run(&self, loggers: &mut Vec<&mut dyn Log<(P::Solution, P::Measure)>>) -> Result<(), Error> {
+        let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        info!("started the evolutionary algorithm with seed {}", seed);
+
+        let population = self.initialize.initialize(self.pop_size, &mut rng);
+        population.iter().for_each(|individual| self.check(individual));
+        info!("initialized the population");
+
+        self.evolve(population, 0, seed, rng, loggers)
+    }
+
+    fn evolve(&self, mut current_generation: Vec<Individual<P>>, first_generation: usize, seed: u64,
+        mut rng: ChaCha8Rng, loggers: &mut Vec<&mut dyn Log<(P::Solution, P::Measure)>>) -> Result<(), Error>
+    {
         use std::time::Instant;
 
         let start = Instant::now();
-        info!("started the evolutionary algorithm");
 
-        let mut current_generation = self.initialize.initialize(self.pop_size);
-        info!("initialized the population");
-        
-        for i in 0..self.generations {
+        for i in first_generation..self.generations {
             let mut next_generation = Vec::new();
             while next_generation.len() < self.pop_size {
-                let parent1 = self.select.select(&current_generation);
-                let parent2 = self.select.select(&current_generation);
-                let mut offspring = self.crossover.crossover(&parent1, &parent2);
-                self.mutate.mutate(&mut offspring);
-                
+                let parent1 = self.select.select(&current_generation, &mut rng);
+                let parent2 = self.select.select(&current_generation, &mut rng);
+                let mut offspring = self.crossover.crossover(parent1, parent2, &mut rng);
+                self.mutate.mutate(&mut offspring, &mut rng);
+                self.check(&offspring);
+
                 for logger in loggers.iter_mut() {
                     logger.log(&(
                         offspring.genotype.clone(),
@@ -105,9 +169,151 @@ where
             }
             current_generation = next_generation;
             info!("finished generation #{}", i + 1);
+
+            if let Some((path, interval, save)) = &self.checkpoint {
+                if (i + 1) % interval == 0 || i + 1 == self.generations {
+                    let checkpoint = Checkpoint::<P> {
+                        generation: i + 1,
+                        population: current_generation,
+                        seed: seed,
+                        word_pos: rng.get_word_pos(),
+                        loggers: loggers.iter().map(|logger| logger.save()).collect(),
+                    };
+                    save(path, &checkpoint)?;
+                    current_generation = checkpoint.population;
+                    info!("saved a checkpoint to {:?}", path);
+                }
+            }
         }
-        
+
         let duration = start.elapsed();
         info!("finished the evolutionary algorithm in {:?}", duration);
+        Ok(())
+    }
+
+    fn check(&self, individual: &Individual<P>) {
+        if let Some(problem) = self.validator {
+            debug_validate(problem, &individual.genotype);
+            debug_assert!(individual.fitness == problem.fitness(&individual.genotype), "stale fitness");
+        }
+    }
+}
+
+/// Checkpoints need the solutions and the measures to be serializable, unlike
+/// the rest of the algorithm.
+impl<'a, P: Problem, I, S, C, M> Evolutionary<'a, P, I, S, C, M>
+where
+    I: Initialize<Problem=P>,
+    S: Select<Problem=P>,
+    C: Crossover<Problem=P>,
+    M: Mutate<Problem=P>,
+    P::Solution: Serialize + DeserializeOwned,
+    P::Measure: Serialize + DeserializeOwned + PartialEq
+{
+    /// Saves the state of the run to `path` every `interval` generations and
+    /// after the last one.
+    pub fn checkpoint(mut self, path: PathBuf, interval: usize) -> Self {
+        assert!(interval > 0, "invalid checkpoint interval: {}", interval);
+        self.checkpoint = Some((path, interval, save_checkpoint::<Checkpoint<P>>));
+        self
+    }
+
+    /// Continues the run from the checkpoint file, restoring the population,
+    /// the random number generator and the state of the loggers.
+    pub fn resume(&self, loggers: &mut Vec<&mut dyn Log<(P::Solution, P::Measure)>>) -> Result<(), Error> {
+        let (path, _, _) = self.checkpoint.as_ref()
+            .ok_or_else(|| format_err!("no checkpoint file configured"))?;
+        let checkpoint: Checkpoint<P> = serde_json::from_str(&read_file(path)?)?;
+        ensure!(checkpoint.loggers.len() == loggers.len(),
+            "checkpoint holds the state of {} loggers, got {}", checkpoint.loggers.len(), loggers.len());
+        for (logger, state) in loggers.iter_mut().zip(checkpoint.loggers) {
+            logger.restore(state)?;
+        }
+        checkpoint.population.iter().for_each(|individual| self.check(individual));
+        let mut rng = ChaCha8Rng::seed_from_u64(checkpoint.seed);
+        rng.set_word_pos(checkpoint.word_pos);
+        info!("resumed the evolutionary algorithm from generation #{}", checkpoint.generation);
+
+        self.evolve(checkpoint.population, checkpoint.generation, checkpoint.seed, rng, loggers)
+    }
+}
+
+/// Writes the checkpoint next to its destination first, so that killing the
+/// program midway never leaves a truncated file behind.
+fn save_checkpoint<T: Serialize>(path: &PathBuf, checkpoint: &T) -> Result<(), Error> {
+    let temporary = path.with_extension("tmp");
+    write_to_file(&temporary, &serde_json::to_string(checkpoint)?)?;
+    std::fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tsp::{TSP, ops, parser::parse_problem_instance};
+
+    /// Copies the first city over the second one, leaving a city out.
+    struct Duplicate;
+
+    impl Mutate for Duplicate {
+        type Problem = TSP;
+
+        fn mutate<R: Rng>(&self, individual: &mut Individual<TSP>, _rng: &mut R) {
+            individual.genotype[1] = individual.genotype[0];
+        }
+    }
+
+    #[test]
+    fn test_resume() {
+        use crate::tsp::logs::{Cohorter, Discoverer};
+
+        let problem = parse_problem_instance(&PathBuf::from("data/berlin52.tsp")).unwrap();
+        let build = |generations: usize, path: &PathBuf| Evolutionary::new(
+            ops::initialize::Random::new(&problem),
+            ops::select::Tournament::new(5),
+            ops::crossover::OX::new(&problem, 0.7),
+            ops::mutate::Inversion::new(&problem, 0.1),
+            20,
+            generations,
+        ).seed(7).checkpoint(path.clone(), 4);
+        let final_state = |path: &PathBuf| {
+            let state: serde_json::Value = serde_json::from_str(&read_file(path).unwrap()).unwrap();
+            std::fs::remove_file(path).unwrap();
+            state
+        };
+
+        let uninterrupted = std::env::temp_dir().join("assg1-uninterrupted.json");
+        let (mut discoverer, mut cohorter) = (Discoverer::new(), Cohorter::new(20, 10));
+        build(10, &uninterrupted).run(&mut vec![&mut discoverer, &mut cohorter]).unwrap();
+
+        // stopped after the checkpoint of generation 4, and resumed with
+        // fresh loggers
+        let interrupted = std::env::temp_dir().join("assg1-interrupted.json");
+        let (mut discoverer, mut cohorter) = (Discoverer::new(), Cohorter::new(20, 10));
+        build(4, &interrupted).run(&mut vec![&mut discoverer, &mut cohorter]).unwrap();
+        let (mut discoverer, mut cohorter) = (Discoverer::new(), Cohorter::new(20, 10));
+        build(10, &interrupted).resume(&mut vec![&mut discoverer, &mut cohorter]).unwrap();
+
+        let (expected, actual) = (final_state(&uninterrupted), final_state(&interrupted));
+        assert_eq!(actual["generation"], 10);
+        assert_eq!(actual["population"], expected["population"]);
+        assert_eq!(actual["loggers"], expected["loggers"]);
+        assert_eq!(actual, expected);
+    }
+
+    // the checks are compiled out of release builds
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "invalid solution")]
+    fn test_validate() {
+        let problem = parse_problem_instance(&PathBuf::from("data/berlin52.tsp")).unwrap();
+        Evolutionary::new(
+            ops::initialize::Random::new(&problem),
+            ops::select::Tournament::new(2),
+            ops::crossover::OX::new(&problem, 0.5),
+            Duplicate,
+            10,
+            1,
+        ).validate(&problem).seed(0).run(&mut vec![]).unwrap();
     }
 }
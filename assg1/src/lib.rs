@@ -0,0 +1,19 @@
+//! Evolutionary algorithms and the problems they solve.
+//!
+//! The [`ea`] module holds the algorithm itself, generic over a
+//! [`Problem`](problem::Problem) and its operators, while the [`tsp`] module
+//! provides the travelling salesperson problem with its parser, operators,
+//! other solvers and loggers.
+
+#![allow(
+    clippy::needless_return,
+    clippy::redundant_field_names,
+    clippy::ptr_arg,
+    clippy::upper_case_acronyms,
+)]
+
+pub mod cvrp;
+pub mod ea;
+pub mod log;
+pub mod problem;
+pub mod tsp;
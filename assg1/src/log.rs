@@ -1,6 +1,19 @@
+use quicli::prelude::*;
+
 pub trait Log<T>
 where
     T: ToOwned
 {
     fn log(&mut self, value: &T);
+
+    /// Captures the state of the logger for a checkpoint. Stateless loggers
+    /// can rely on the default.
+    fn save(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Restores the state captured by `save`.
+    fn restore(&mut self, _state: serde_json::Value) -> Result<(), Error> {
+        Ok(())
+    }
 }
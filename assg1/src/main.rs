@@ -1,10 +1,20 @@
+#![allow(
+    clippy::needless_return,
+    clippy::redundant_field_names,
+    clippy::ptr_arg,
+    clippy::upper_case_acronyms,
+)]
+
 use quicli::prelude::*;
 use structopt::StructOpt;
 
-mod ea;
-mod tsp;
-mod problem;
-mod log;
+use assg1::{cvrp, log, tsp};
+
+mod bench;
+mod config;
+mod compare;
+mod stats;
+mod tune;
 
 use std::path::PathBuf;
 
@@ -12,40 +22,559 @@ use std::path::PathBuf;
 struct Config {
     #[structopt(flatten)]
     verbosity: Verbosity,
+    #[structopt(flatten)]
+    run: Run,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+/// Options of the evolutionary algorithm, which runs when no subcommand is
+/// given as well as with the `run` subcommand.
+#[derive(Debug, StructOpt)]
+struct Run {
     /// TSP instance file
     #[structopt(long = "input", short = "i", name = "TSP FILE", parse(from_os_str))]
-    tsp_path: PathBuf,
+    tsp_path: Option<PathBuf>,
+    /// Keep the distances with this many decimals instead of rounding them
+    #[structopt(long = "decimals", name = "DECIMALS", default_value = "0")]
+    decimals: u32,
+    /// Sum the exact distances into floating-point tour lengths instead of rounding them
+    #[structopt(long = "exact", raw(conflicts_with = r#""DECIMALS""#))]
+    exact: bool,
     /// Cohorter output file
     #[structopt(long = "output", short = "o", name = "OUTPUT FILE", parse(from_os_str))]
     output_path: Option<PathBuf>,
+    /// Configuration of the evolutionary algorithm
+    #[structopt(long = "config", short = "c", name = "CONFIGURATION", default_value = "")]
+    configuration: config::Configuration,
+    /// Seed of the first run, incremented for each subsequent run
+    #[structopt(long = "seed", name = "SEED")]
+    seed: Option<u64>,
+    /// Checkpoint file, suffixed with the index of the run
+    #[structopt(long = "checkpoint", name = "CHECKPOINT FILE", parse(from_os_str))]
+    checkpoint_path: Option<PathBuf>,
+    /// Number of generations between checkpoints
+    #[structopt(long = "checkpoint-interval", name = "GENERATIONS", default_value = "10")]
+    checkpoint_interval: usize,
+    /// Resume from the latest checkpoint instead of starting over, with the seed of the interrupted runs
+    #[structopt(long = "resume", raw(requires_all = r#"&["CHECKPOINT FILE", "SEED"]"#))]
+    resume: bool,
+    /// SVG output file with the best tour found
+    #[structopt(long = "tour-svg", name = "TOUR SVG FILE", parse(from_os_str))]
+    tour_svg_path: Option<PathBuf>,
+    /// SVG output file with the best, average and worst fitness in every generation
+    #[structopt(long = "curves-svg", name = "CURVES SVG FILE", parse(from_os_str))]
+    curves_svg_path: Option<PathBuf>,
+    #[structopt(flatten)]
+    profiling: Profiling,
 }
 
-fn main() -> CliResult {
-    let config = Config::from_args();
-    config.verbosity.setup_env_logger(&env!("CARGO_PKG_NAME"))?;
+/// Outputs of the best fitness against the evaluations and the wall time.
+#[derive(Debug, StructOpt)]
+struct Profiling {
+    /// Anytime output file with every improvement of every run
+    #[structopt(long = "anytime", name = "ANYTIME FILE", parse(from_os_str))]
+    anytime_path: Option<PathBuf>,
+    /// Time-to-target output file with the run-time distribution
+    #[structopt(long = "ttt", name = "TTT FILE", parse(from_os_str))]
+    ttt_path: Option<PathBuf>,
+    /// Target of the time-to-target output, the known optimum by default
+    #[structopt(long = "target", name = "TARGET")]
+    target: Option<u64>,
+}
+
+impl Profiling {
+    fn dump<L: tsp::Distance>(&self, problem: &tsp::TSP<L>, tsp_path: &PathBuf, anytime: &tsp::logs::Anytime<L>)
+        -> Result<(), Error>
+    {
+        if let Some(path) = &self.anytime_path {
+            anytime.dump(path)?;
+        }
+        if let Some(path) = &self.ttt_path {
+            let instance = tsp_path.file_stem().unwrap().to_string_lossy();
+            let target = self.target
+                .or_else(|| tsp::optima::known_optimum(&instance).filter(|_| !L::EXACT && problem.decimals() == 0))
+                .ok_or_else(|| format_err!("no known optimum of {}, provide a target", instance))?;
+            anytime.dump_ttt(L::from_u64(target), path)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Runs the evolutionary algorithm on a single instance, as without a subcommand
+    #[structopt(name = "run")]
+    Run(Run),
+    /// Compares configurations of the evolutionary algorithm with statistical tests
+    #[structopt(name = "compare")]
+    Compare {
+        /// TSP instance files
+        #[structopt(long = "input", short = "i", name = "TSP FILE", parse(from_os_str))]
+        tsp_paths: Vec<PathBuf>,
+        /// Configurations to compare
+        #[structopt(long = "config", short = "c", name = "CONFIGURATION")]
+        configurations: Vec<config::Configuration>,
+        /// Results of earlier comparisons, loaded instead of running the configurations
+        #[structopt(long = "results", short = "r", name = "RESULTS FILE", parse(from_os_str))]
+        results_paths: Vec<PathBuf>,
+        /// Output file for the results of every run
+        #[structopt(long = "output", short = "o", name = "OUTPUT FILE", parse(from_os_str))]
+        output_path: Option<PathBuf>,
+        /// Number of runs of each configuration on each instance
+        #[structopt(long = "runs", name = "RUNS", default_value = "10")]
+        runs: usize,
+        /// Seed of the first run, shared by all configurations
+        #[structopt(long = "seed", name = "SEED")]
+        seed: Option<u64>,
+    },
+    /// Runs a configuration on every instance in a directory or matching a glob pattern
+    #[structopt(name = "bench")]
+    Bench {
+        /// Directory with TSP instance files, or a glob pattern matching them
+        #[structopt(long = "input", short = "i", name = "TSP FILES", default_value = "data")]
+        tsp_pattern: String,
+        /// Configuration of the evolutionary algorithm
+        #[structopt(long = "config", short = "c", name = "CONFIGURATION", default_value = "")]
+        configuration: config::Configuration,
+        /// Output file for the consolidated table
+        #[structopt(long = "output", short = "o", name = "OUTPUT FILE", parse(from_os_str))]
+        output_path: Option<PathBuf>,
+        /// Number of runs on each instance
+        #[structopt(long = "runs", name = "RUNS", default_value = "10")]
+        runs: usize,
+        /// Seed of the first run on each instance
+        #[structopt(long = "seed", name = "SEED")]
+        seed: Option<u64>,
+        /// Population size per node of the instance, overriding the configuration
+        #[structopt(long = "pop-per-node", name = "POP FACTOR")]
+        pop_per_node: Option<f64>,
+        /// Number of generations per node of the instance, overriding the configuration
+        #[structopt(long = "gen-per-node", name = "GEN FACTOR")]
+        gen_per_node: Option<f64>,
+    },
+    /// Tunes the configuration by racing configurations sampled from parameter ranges
+    #[structopt(name = "tune")]
+    Tune {
+        /// Directory with the training TSP instance files, or a glob pattern matching them
+        #[structopt(long = "input", short = "i", name = "TSP FILES", default_value = "data")]
+        tsp_pattern: String,
+        /// Parameter ranges, e.g. select=tournament:2..50,crossover=ox:0.5..1.0|cx:0.5..1.0
+        #[structopt(long = "space", name = "SPACE",
+            default_value = "select=tournament:2..50,crossover=ox:0.5..1.0,mutate=inversion:0.01..0.5")]
+        space: tune::Space,
+        /// Configuration providing the keys missing from the space
+        #[structopt(long = "config", short = "c", name = "CONFIGURATION", default_value = "")]
+        configuration: config::Configuration,
+        /// Number of sampled configurations
+        #[structopt(long = "candidates", name = "CANDIDATES", default_value = "20")]
+        candidates: usize,
+        /// Maximum number of runs of the evolutionary algorithm
+        #[structopt(long = "budget", name = "BUDGET", default_value = "200")]
+        budget: usize,
+        /// Number of steps before the first elimination
+        #[structopt(long = "first-test", name = "STEPS", default_value = "5")]
+        first_test: usize,
+        /// Significance level of the eliminations
+        #[structopt(long = "alpha", name = "ALPHA", default_value = "0.05")]
+        alpha: f64,
+        /// Output file for the evaluation log
+        #[structopt(long = "output", short = "o", name = "OUTPUT FILE", parse(from_os_str))]
+        output_path: Option<PathBuf>,
+        /// Seed of the sampling and of the first step of the race
+        #[structopt(long = "seed", name = "SEED")]
+        seed: Option<u64>,
+    },
+    /// Runs Ant System or MAX-MIN Ant System on a single instance
+    #[structopt(name = "aco")]
+    Aco {
+        /// TSP instance file
+        #[structopt(long = "input", short = "i", name = "TSP FILE", parse(from_os_str))]
+        tsp_path: PathBuf,
+        /// Keep the distances with this many decimals instead of rounding them
+        #[structopt(long = "decimals", name = "DECIMALS", default_value = "0")]
+        decimals: u32,
+        /// Cohorter output file
+        #[structopt(long = "output", short = "o", name = "OUTPUT FILE", parse(from_os_str))]
+        output_path: Option<PathBuf>,
+        /// Number of ants constructing a tour in every iteration
+        #[structopt(long = "ants", name = "ANTS", default_value = "25")]
+        ants: usize,
+        /// Number of iterations
+        #[structopt(long = "iterations", name = "ITERATIONS", default_value = "250")]
+        iterations: usize,
+        /// Weight of the pheromone
+        #[structopt(long = "alpha", name = "ALPHA", default_value = "1")]
+        alpha: f64,
+        /// Weight of the visibility, i.e. the inverse of the distance
+        #[structopt(long = "beta", name = "BETA", default_value = "2")]
+        beta: f64,
+        /// Evaporation rate of the pheromone
+        #[structopt(long = "rho", name = "RHO", default_value = "0.5")]
+        rho: f64,
+        /// Use MAX-MIN Ant System instead of Ant System
+        #[structopt(long = "max-min")]
+        max_min: bool,
+        /// Improve every constructed tour with 2-opt
+        #[structopt(long = "two-opt")]
+        two_opt: bool,
+        /// Seed of the first run, incremented for each subsequent run
+        #[structopt(long = "seed", name = "SEED")]
+        seed: Option<u64>,
+        #[structopt(flatten)]
+        profiling: Profiling,
+    },
+    /// Runs the Lin–Kernighan and Or-opt local search from random tours
+    #[structopt(name = "opt")]
+    Opt {
+        /// TSP instance file
+        #[structopt(long = "input", short = "i", name = "TSP FILE", parse(from_os_str))]
+        tsp_path: PathBuf,
+        /// Keep the distances with this many decimals instead of rounding them
+        #[structopt(long = "decimals", name = "DECIMALS", default_value = "0")]
+        decimals: u32,
+        /// Number of random tours improved in every run
+        #[structopt(long = "starts", name = "STARTS", default_value = "100")]
+        starts: usize,
+        /// Number of nearest neighbors considered by the moves
+        #[structopt(long = "candidates", name = "CANDIDATES", default_value = "10")]
+        candidates: usize,
+        /// Largest number of 2-opt moves chained into a Lin–Kernighan move, 1 for plain 2-opt
+        #[structopt(long = "depth", name = "DEPTH", default_value = "10")]
+        depth: usize,
+        /// Use Lin–Kernighan moves only
+        #[structopt(long = "no-or-opt")]
+        no_or_opt: bool,
+        /// Seed of the first run, incremented for each subsequent run
+        #[structopt(long = "seed", name = "SEED")]
+        seed: Option<u64>,
+        #[structopt(flatten)]
+        profiling: Profiling,
+    },
+    /// Runs a tour construction heuristic from evenly spread starting nodes
+    #[structopt(name = "construct")]
+    Construct {
+        /// TSP instance file
+        #[structopt(long = "input", short = "i", name = "TSP FILE", parse(from_os_str))]
+        tsp_path: PathBuf,
+        /// Keep the distances with this many decimals instead of rounding them
+        #[structopt(long = "decimals", name = "DECIMALS", default_value = "0")]
+        decimals: u32,
+        /// Heuristic: nearest-insertion, farthest-insertion, cheapest-insertion, savings or christofides
+        #[structopt(long = "heuristic", short = "H", name = "HEURISTIC")]
+        heuristic: tsp::construct::Heuristic,
+        /// Number of starting nodes
+        #[structopt(long = "starts", name = "STARTS", default_value = "10")]
+        starts: usize,
+        #[structopt(flatten)]
+        profiling: Profiling,
+    },
+    /// Runs the evolutionary algorithm on a capacitated vehicle routing instance
+    #[structopt(name = "cvrp")]
+    Cvrp {
+        /// CVRP instance file
+        #[structopt(long = "input", short = "i", name = "VRP FILE", parse(from_os_str))]
+        vrp_path: PathBuf,
+        /// Cohorter output file
+        #[structopt(long = "output", short = "o", name = "OUTPUT FILE", parse(from_os_str))]
+        output_path: Option<PathBuf>,
+        /// Configuration of the evolutionary algorithm, with the random initialization
+        #[structopt(long = "config", short = "c", name = "CONFIGURATION", default_value = "")]
+        configuration: config::Configuration,
+        /// Seed of the first run, incremented for each subsequent run
+        #[structopt(long = "seed", name = "SEED")]
+        seed: Option<u64>,
+        /// Output file with the routes of the best solution, in the CVRPLIB format
+        #[structopt(long = "routes", name = "ROUTES FILE", parse(from_os_str))]
+        routes_path: Option<PathBuf>,
+    },
+    /// Generates a random TSP instance file
+    #[structopt(name = "generate")]
+    Generate {
+        /// Layout of the nodes: uniform, clustered[:CLUSTERS], grid or geo
+        #[structopt(long = "layout", short = "l", name = "LAYOUT", default_value = "uniform")]
+        layout: tsp::generator::Layout,
+        /// Number of nodes
+        #[structopt(long = "dimension", short = "d", name = "DIMENSION")]
+        dimension: usize,
+        /// Seed of the generator
+        #[structopt(long = "seed", name = "SEED")]
+        seed: Option<u64>,
+        /// Name of the instance, derived from the layout and the dimension by default
+        #[structopt(long = "name", name = "NAME")]
+        name: Option<String>,
+        /// TSP output file
+        #[structopt(long = "output", short = "o", name = "OUTPUT FILE", parse(from_os_str))]
+        output_path: PathBuf,
+    },
+}
+
+const RUNS: usize = 10;
+
+/// When the distances keep decimals or are exact, prints the length of the
+/// best tour with the distances rounded the way TSPLIB does, to compare the two.
+fn print_rounded<L: tsp::Distance>(problem: &tsp::TSP<L>, discoverer: &tsp::logs::Discoverer<L>) {
+    if L::EXACT || problem.decimals() > 0 {
+        println!("% rounded length of the best tour: {}", problem.rounded_length(&discoverer.best_solution));
+    }
+}
+
+/// Runs the evolutionary algorithm `RUNS` times with the options of `run`,
+/// summing the tour lengths in `L`.
+fn evolve<L: tsp::Distance>(problem: tsp::TSP<L>, tsp_path: &PathBuf, run: &Run) -> Result<(), Error> {
+    let Run {
+        output_path, configuration, seed, checkpoint_path, checkpoint_interval, resume,
+        tour_svg_path, curves_svg_path, profiling, ..
+    } = run;
 
-    use tsp::{parser::parse_problem_instance, ops};
-    
-    let problem = parse_problem_instance(&config.tsp_path)?;
+    let checkpoint_path = |run: usize| checkpoint_path.as_ref()
+        .map(|path| path.with_extension(format!("{}", run)));
+    let first_run = if *resume {
+        (0..RUNS).rev()
+            .find(|run| checkpoint_path(*run).unwrap().exists())
+            .ok_or_else(|| format_err!("no checkpoint found"))?
+    } else {
+        0
+    };
+    let seed = seed.unwrap_or_else(rand::random);
 
     let mut discoverer = tsp::logs::Discoverer::new();
-    let mut cohorter = tsp::logs::Cohorter::new(1000, 250);
-    for _ in 0..10 {
-        let evolutionary = ea::Evolutionary::new(
-            ops::initialize::Random::new(&problem),
-            ops::select::Tournament::new(15),
-            ops::crossover::OX::new(&problem, 0.8),
-            ops::mutate::Inversion::new(&problem, 0.1),
-            1000,
-            250,
-        );
-        evolutionary.run(&mut vec![&mut discoverer, &mut cohorter]);
+    let mut cohorter = tsp::logs::Cohorter::new(configuration.pop_size, configuration.generations);
+    let mut anytime = tsp::logs::Anytime::new("evaluations");
+    for run in first_run..RUNS {
+        let mut evolutionary = configuration.build(&problem)
+            .seed(seed.wrapping_add(run as u64));
+        if let Some(path) = checkpoint_path(run) {
+            evolutionary = evolutionary.checkpoint(path, *checkpoint_interval);
+        }
+        let mut loggers: Vec<&mut dyn log::Log<(Vec<usize>, L)>> = vec![&mut discoverer, &mut cohorter, &mut anytime];
+        if *resume && run == first_run {
+            evolutionary.resume(&mut loggers)?;
+        } else {
+            evolutionary.run(&mut loggers)?;
+        }
         discoverer.carry();
         cohorter.carry();
+        anytime.carry();
     }
     discoverer.print();
-    if let Some(path) = config.output_path {
-        cohorter.dump(&path)?;
+    print_rounded(&problem, &discoverer);
+    profiling.dump(&problem, tsp_path, &anytime)?;
+    if let Some(path) = output_path {
+        cohorter.dump(path)?;
+    }
+    if let Some(path) = tour_svg_path {
+        write_to_file(path, &tsp::render::tour(&problem, &discoverer.best_solution))?;
+    }
+    if let Some(path) = curves_svg_path {
+        let (bests, avgs, worsts) = cohorter.curves();
+        write_to_file(path, &tsp::render::convergence(&bests, &avgs, &worsts))?;
+    }
+    Ok(())
+}
+
+fn main() -> CliResult {
+    let config = Config::from_args();
+    config.verbosity.setup_env_logger(env!("CARGO_PKG_NAME"))?;
+
+    let command = match config.command {
+        Some(command) => {
+            if config.run.tsp_path.is_some() {
+                return Err(format_err!("the options of the evolutionary algorithm go after the run subcommand").into());
+            }
+            command
+        },
+        None => Command::Run(config.run),
+    };
+    match command {
+        Command::Run(run) => {
+            let tsp_path = run.tsp_path.as_ref().ok_or_else(|| format_err!("the --input argument is required"))?;
+            if run.checkpoint_interval == 0 {
+                return Err(format_err!("the checkpoint interval must be at least 1").into());
+            }
+            let problem = tsp::parser::parse_with_decimals(tsp_path, run.decimals)?;
+            if run.exact {
+                evolve(problem.measured::<f64>(), tsp_path, &run)?;
+            } else {
+                evolve(problem, tsp_path, &run)?;
+            }
+        },
+        Command::Compare { tsp_paths, configurations, results_paths, output_path, runs, seed } => {
+            let mut records = Vec::new();
+            for path in &results_paths {
+                records.extend(compare::load_records(path)?);
+            }
+            if !configurations.is_empty() {
+                if tsp_paths.is_empty() {
+                    return Err(format_err!("no instances to run the configurations on").into());
+                }
+                let seed = seed.unwrap_or_else(rand::random);
+                records.extend(compare::run_records(&configurations, &tsp_paths, runs, seed)?);
+            }
+            if records.is_empty() {
+                return Err(format_err!("nothing to compare, provide configurations or results").into());
+            }
+            if let Some(path) = output_path {
+                compare::dump_records(&records, &path)?;
+            }
+            compare::print_summary(&records);
+        },
+        Command::Bench {
+            tsp_pattern, configuration, output_path, runs, seed, pop_per_node, gen_per_node
+        } => {
+            let instance_paths = bench::instance_paths(&tsp_pattern)?;
+            let scaling = bench::Scaling {
+                pop_per_node: pop_per_node,
+                gen_per_node: gen_per_node,
+            };
+            let seed = seed.unwrap_or_else(rand::random);
+            let rows = bench::benchmark(&configuration, &instance_paths, runs, seed, &scaling)?;
+            bench::print_table(&rows);
+            if let Some(path) = output_path {
+                bench::dump_table(&rows, &path)?;
+            }
+        },
+        Command::Tune {
+            tsp_pattern, space, configuration, candidates, budget, first_test, alpha, output_path, seed
+        } => {
+            use rand::SeedableRng;
+            use tsp::parser::parse_problem_instance;
+
+            let problems = bench::instance_paths(&tsp_pattern)?.iter()
+                .map(|path| Ok((
+                    path.file_stem().unwrap().to_string_lossy().into_owned(),
+                    parse_problem_instance(path)?,
+                )))
+                .collect::<Result<Vec<(String, tsp::TSP)>, Error>>()?;
+            let seed = seed.unwrap_or_else(rand::random);
+            let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+            let candidates = (0..candidates)
+                .map(|_| space.sample(&configuration, &mut rng))
+                .collect::<Result<Vec<config::Configuration>, Error>>()?;
+
+            let race = tune::race(candidates, &problems, budget, first_test, alpha, seed)?;
+            tune::print_survivors(&race);
+            if let Some(path) = output_path {
+                tune::dump_evaluations(&race, &path)?;
+            }
+            println!("{}", race.survivors[0].configuration);
+        },
+        Command::Aco {
+            tsp_path, decimals, output_path, ants, iterations, alpha, beta, rho, max_min, two_opt, seed, profiling
+        } => {
+            use tsp::{aco, parser::parse_with_decimals};
+
+            if ants == 0 || iterations == 0 {
+                return Err(format_err!("at least one ant and one iteration are needed").into());
+            }
+            if !(rho > 0.0 && rho <= 1.0) {
+                return Err(format_err!("the evaporation rate must be in (0, 1]").into());
+            }
+            let problem = parse_with_decimals(&tsp_path, decimals)?;
+            let variant = if max_min { aco::Variant::MaxMin } else { aco::Variant::AntSystem };
+            let seed = seed.unwrap_or_else(rand::random);
+
+            let mut discoverer = tsp::logs::Discoverer::new();
+            let mut cohorter = tsp::logs::Cohorter::new(ants, iterations);
+            let mut anytime = tsp::logs::Anytime::new("tours");
+            for run in 0..RUNS {
+                aco::AntColony::new(&problem, variant, ants, iterations, alpha, beta, rho)
+                    .two_opt(two_opt)
+                    .seed(seed.wrapping_add(run as u64))
+                    .run(&mut vec![&mut discoverer, &mut cohorter, &mut anytime]);
+                discoverer.carry();
+                cohorter.carry();
+                anytime.carry();
+            }
+            discoverer.print();
+            print_rounded(&problem, &discoverer);
+            profiling.dump(&problem, &tsp_path, &anytime)?;
+            if let Some(path) = output_path {
+                cohorter.dump(&path)?;
+            }
+        },
+        Command::Opt { tsp_path, decimals, starts, candidates, depth, no_or_opt, seed, profiling } => {
+            use rand::SeedableRng;
+            use tsp::{local::LocalSearch, parser::parse_with_decimals};
+
+            if starts == 0 {
+                return Err(format_err!("at least one start is needed").into());
+            }
+            if depth == 0 {
+                return Err(format_err!("the depth must be at least 1").into());
+            }
+            let problem = parse_with_decimals(&tsp_path, decimals)?;
+            let local_search = LocalSearch::new(&problem, candidates).depth(depth).or_opt(!no_or_opt);
+            let seed = seed.unwrap_or_else(rand::random);
+
+            let mut discoverer = tsp::logs::Discoverer::new();
+            let mut anytime = tsp::logs::Anytime::new("local optima");
+            for run in 0..RUNS {
+                let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed.wrapping_add(run as u64));
+                local_search.run(starts, &mut rng, &mut vec![&mut discoverer, &mut anytime]);
+                discoverer.carry();
+                anytime.carry();
+            }
+            discoverer.print();
+            print_rounded(&problem, &discoverer);
+            profiling.dump(&problem, &tsp_path, &anytime)?;
+        },
+        Command::Construct { tsp_path, decimals, heuristic, starts, profiling } => {
+            use tsp::{construct::Construction, parser::parse_with_decimals};
+
+            if starts == 0 {
+                return Err(format_err!("at least one start is needed").into());
+            }
+            let problem = parse_with_decimals(&tsp_path, decimals)?;
+            // the heuristics are deterministic, so a single run is enough
+            let mut discoverer = tsp::logs::Discoverer::new();
+            let mut anytime = tsp::logs::Anytime::new("constructions");
+            Construction::new(&problem, heuristic, starts).run(&mut vec![&mut discoverer, &mut anytime]);
+            discoverer.carry();
+            anytime.carry();
+            discoverer.print();
+            print_rounded(&problem, &discoverer);
+            profiling.dump(&problem, &tsp_path, &anytime)?;
+        },
+        Command::Cvrp { vrp_path, output_path, configuration, seed, routes_path } => {
+            let problem = cvrp::parser::parse_problem_instance(&vrp_path)?;
+            let seed = seed.unwrap_or_else(rand::random);
+
+            let mut discoverer = tsp::logs::Discoverer::new();
+            let mut cohorter = tsp::logs::Cohorter::new(configuration.pop_size, configuration.generations);
+            for run in 0..RUNS {
+                configuration.build_permutation(&problem)?
+                    .seed(seed.wrapping_add(run as u64))
+                    .run(&mut vec![&mut discoverer, &mut cohorter])?;
+                discoverer.carry();
+                cohorter.carry();
+            }
+            discoverer.print();
+            if let Some(path) = output_path {
+                cohorter.dump(&path)?;
+            }
+            if let Some(path) = routes_path {
+                let (cost, routes) = problem.split(&discoverer.best_solution);
+                write_to_file(&path, &cvrp::format_solution(&routes, cost))?;
+            }
+        },
+        Command::Generate { layout, dimension, seed, name, output_path } => {
+            use rand::SeedableRng;
+            use tsp::generator::{generate_coordinates, format_instance};
+
+            let seed = seed.unwrap_or_else(rand::random);
+            let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+            let coordinates = generate_coordinates(layout, dimension, &mut rng);
+            let name = name.unwrap_or_else(|| match layout {
+                tsp::generator::Layout::Uniform => format!("uniform{}", dimension),
+                tsp::generator::Layout::Clustered(clusters) => format!("clustered{}x{}", clusters, dimension),
+                tsp::generator::Layout::Grid => format!("grid{}", dimension),
+                tsp::generator::Layout::Geographical => format!("geo{}", dimension),
+            });
+            let comment = format!("{:?} layout generated with seed {}", layout, seed);
+            write_to_file(&output_path, &format_instance(&name, &comment, &layout.coord_system(), &coordinates))?;
+            info!("generated {} to {:?}", name, output_path);
+        }
     }
 
     Ok(())
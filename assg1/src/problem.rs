@@ -1,6 +1,148 @@
+use quicli::prelude::*;
+use serde::{Serialize, de::DeserializeOwned};
+use std::fmt;
+
 pub trait Problem {
     type Solution: Clone;
     type Measure: Clone;
 
     fn fitness(&self, solution: &Self::Solution) -> Self::Measure;
+
+    /// Checks that the solution is feasible, describing what is wrong with it
+    /// otherwise. Every solution is feasible unless the problem says otherwise.
+    fn validate(&self, _solution: &Self::Solution) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Panics with the description of an infeasible solution, in debug builds
+/// only, so that a buggy operator fails instead of just looking bad.
+pub fn debug_validate<P: Problem>(problem: &P, solution: &P::Solution) {
+    if cfg!(debug_assertions) {
+        if let Err(error) = problem.validate(solution) {
+            panic!("invalid solution: {}", error);
+        }
+    }
+}
+
+/// A tour length, summed either in integers or in floating point, which the
+/// permutation operators compare and turn into selection weights, and the
+/// loggers save in checkpoints.
+pub trait Length: Copy + PartialOrd + fmt::Debug + fmt::Display + Serialize + DeserializeOwned {
+    /// The length of a solution that is not a tour, longer than any tour.
+    const INFEASIBLE: Self;
+
+    fn to_f64(self) -> f64;
+
+    /// The length from an integer one, such as a target given on the command
+    /// line.
+    fn from_u64(value: u64) -> Self;
+}
+
+impl Length for u64 {
+    const INFEASIBLE: u64 = u64::MAX;
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_u64(value: u64) -> u64 {
+        value
+    }
+}
+
+impl Length for f64 {
+    const INFEASIBLE: f64 = f64::INFINITY;
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn from_u64(value: u64) -> f64 {
+        value as f64
+    }
+}
+
+/// A problem whose solutions are orderings of `size` elements, which the
+/// permutation operators of the evolutionary algorithm work on.
+pub trait Permutation: Problem<Solution = Vec<usize>, Measure = <Self as Permutation>::Length> {
+    type Length: Length;
+
+    fn size(&self) -> usize;
+}
+
+/// What keeps a solution from being a permutation of `0..size`.
+#[derive(Debug, PartialEq)]
+pub struct InvalidPermutation {
+    pub size: usize,
+    pub length: usize,
+    /// Elements appearing more than once.
+    pub duplicated: Vec<usize>,
+    /// Elements of `0..size` that do not appear.
+    pub missing: Vec<usize>,
+    /// Elements outside of `0..size`.
+    pub out_of_range: Vec<usize>,
+}
+
+impl fmt::Display for InvalidPermutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected a permutation of {} elements", self.size)?;
+        if self.length != self.size {
+            write!(f, ", got {} elements", self.length)?;
+        }
+        if !self.duplicated.is_empty() {
+            write!(f, ", duplicated {:?}", self.duplicated)?;
+        }
+        if !self.missing.is_empty() {
+            write!(f, ", missing {:?}", self.missing)?;
+        }
+        if !self.out_of_range.is_empty() {
+            write!(f, ", out of range {:?}", self.out_of_range)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvalidPermutation {}
+
+pub fn validate_permutation(solution: &[usize], size: usize) -> Result<(), InvalidPermutation> {
+    let mut counts = vec![0; size];
+    let mut out_of_range = Vec::new();
+    for element in solution {
+        match counts.get_mut(*element) {
+            Some(count) => *count += 1,
+            None => out_of_range.push(*element),
+        }
+    }
+    let duplicated: Vec<usize> = (0..size).filter(|element| counts[*element] > 1).collect();
+    let missing: Vec<usize> = (0..size).filter(|element| counts[*element] == 0).collect();
+    if solution.len() == size && duplicated.is_empty() && missing.is_empty() && out_of_range.is_empty() {
+        Ok(())
+    } else {
+        Err(InvalidPermutation {
+            size: size,
+            length: solution.len(),
+            duplicated: duplicated,
+            missing: missing,
+            out_of_range: out_of_range,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_permutation() {
+        assert_eq!(validate_permutation(&[2, 0, 1], 3), Ok(()));
+        let error = validate_permutation(&[2, 0, 2, 7], 4).unwrap_err();
+        assert_eq!(error.duplicated, vec![2]);
+        assert_eq!(error.missing, vec![1, 3]);
+        assert_eq!(error.out_of_range, vec![7]);
+        assert_eq!(error.to_string(),
+            "expected a permutation of 4 elements, duplicated [2], missing [1, 3], out of range [7]");
+        assert_eq!(validate_permutation(&[0, 1], 3).unwrap_err().to_string(),
+            "expected a permutation of 3 elements, got 2 elements, missing [2]");
+    }
 }
@@ -0,0 +1,296 @@
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Population standard deviation, the same one reported by `Discoverer::print`.
+pub fn std(values: &[f64]) -> f64 {
+    let avg = mean(values);
+    let variance = values.iter().map(|value| {
+        let diff = avg - value;
+        diff * diff
+    }).sum::<f64>() / values.len() as f64;
+    variance.sqrt()
+}
+
+/// Ranks the values starting from 1, assigning tied values the average of the
+/// ranks they span.
+pub fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|a, b| values[*a].partial_cmp(&values[*b]).unwrap());
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && values[order[j + 1]] == values[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for k in i..=j {
+            ranks[order[k]] = rank;
+        }
+        i = j + 1;
+    }
+    return ranks;
+}
+
+pub struct MannWhitney {
+    pub u: f64,
+    pub z: f64,
+    pub p: f64,
+}
+
+/// Two-sided Wilcoxon rank-sum (Mann–Whitney U) test using the normal
+/// approximation with tie and continuity corrections.
+pub fn mann_whitney(a: &[f64], b: &[f64]) -> MannWhitney {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    let pooled: Vec<f64> = a.iter().chain(b.iter()).cloned().collect();
+    let pooled_ranks = ranks(&pooled);
+    let rank_sum: f64 = pooled_ranks[..a.len()].iter().sum();
+    let u = rank_sum - n1 * (n1 + 1.0) / 2.0;
+
+    let n = n1 + n2;
+    let ties = {
+        let mut sorted = pooled.clone();
+        sorted.sort_by(|x, y| x.partial_cmp(y).unwrap());
+        let mut ties = 0.0;
+        let mut i = 0;
+        while i < sorted.len() {
+            let mut j = i;
+            while j + 1 < sorted.len() && sorted[j + 1] == sorted[i] {
+                j += 1;
+            }
+            let t = (j - i + 1) as f64;
+            ties += t * t * t - t;
+            i = j + 1;
+        }
+        ties
+    };
+    let mu = n1 * n2 / 2.0;
+    let sigma = (n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)))).sqrt();
+    let z = if sigma > 0.0 {
+        let diff = u - mu;
+        (diff - 0.5 * diff.signum()) / sigma
+    } else {
+        0.0
+    };
+    MannWhitney {
+        u: u,
+        z: z,
+        p: (2.0 * (1.0 - normal_cdf(z.abs()))).min(1.0),
+    }
+}
+
+/// Vargha–Delaney A12 effect size, the probability that a value drawn from `a`
+/// is smaller (i.e. a better tour) than one drawn from `b`, counting ties as
+/// half.
+pub fn vargha_delaney(a: &[f64], b: &[f64]) -> f64 {
+    let mut wins = 0.0;
+    for x in a {
+        for y in b {
+            if x < y {
+                wins += 1.0;
+            } else if x == y {
+                wins += 0.5;
+            }
+        }
+    }
+    wins / (a.len() * b.len()) as f64
+}
+
+pub struct Friedman {
+    pub average_ranks: Vec<f64>,
+    pub chi_squared: f64,
+    pub p: f64,
+}
+
+/// Friedman test over `blocks`, where each block holds one value per treatment
+/// (e.g. the mean result of every configuration on a single instance). Lower
+/// values get lower ranks.
+pub fn friedman(blocks: &[Vec<f64>]) -> Friedman {
+    let n = blocks.len() as f64;
+    let k = blocks[0].len();
+    let mut average_ranks = vec![0.0; k];
+    for block in blocks {
+        assert_eq!(block.len(), k, "mismatched block sizes");
+        for (total, rank) in average_ranks.iter_mut().zip(ranks(block)) {
+            *total += rank / n;
+        }
+    }
+    let k = k as f64;
+    let chi_squared = 12.0 * n / (k * (k + 1.0))
+        * average_ranks.iter().map(|rank| rank * rank).sum::<f64>()
+        - 3.0 * n * (k + 1.0);
+    Friedman {
+        p: chi_squared_sf(chi_squared, k - 1.0),
+        average_ranks: average_ranks,
+        chi_squared: chi_squared,
+    }
+}
+
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / std::f64::consts::SQRT_2)
+}
+
+/// Inverse of `normal_cdf`, found by bisection.
+pub fn normal_quantile(p: f64) -> f64 {
+    assert!(p > 0.0 && p < 1.0, "invalid probability: {}", p);
+    let (mut low, mut high) = (-10.0, 10.0);
+    for _ in 0..100 {
+        let middle = (low + high) / 2.0;
+        if normal_cdf(middle) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Complementary error function with a fractional error below 1.2e-7.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t * (-z * z - 1.26551223 + t * (1.00002368 + t * (0.37409196 + t * (0.09678418
+        + t * (-0.18628806 + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587
+        + t * (-0.82215223 + t * 0.17087277))))))))).exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// Survival function of the chi-squared distribution with `dof` degrees of
+/// freedom.
+pub fn chi_squared_sf(x: f64, dof: f64) -> f64 {
+    if x <= 0.0 {
+        1.0
+    } else {
+        upper_regularized_gamma(dof / 2.0, x / 2.0)
+    }
+}
+
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146, -86.50532032941677, 24.01409824083091,
+        -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000000000190015;
+    let mut y = x;
+    for coefficient in COEFFICIENTS.iter() {
+        y += 1.0;
+        series += coefficient / y;
+    }
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+fn upper_regularized_gamma(a: f64, x: f64) -> f64 {
+    const EPSILON: f64 = 1e-12;
+    const ITERATIONS: usize = 500;
+
+    let prefix = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut denominator = a;
+        for _ in 0..ITERATIONS {
+            denominator += 1.0;
+            term *= x / denominator;
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        1.0 - sum * prefix
+    } else {
+        let tiny = f64::MIN_POSITIVE / EPSILON;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..ITERATIONS {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPSILON {
+                break;
+            }
+        }
+        prefix * h
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() < tolerance, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn test_ranks() {
+        assert_eq!(ranks(&[10.0, 30.0, 20.0, 20.0]), vec![1.0, 4.0, 2.5, 2.5]);
+    }
+
+    #[test]
+    fn test_normal_cdf() {
+        assert_close(normal_cdf(0.0), 0.5, 1e-7);
+        assert_close(normal_cdf(1.959964), 0.975, 1e-6);
+        assert_close(normal_cdf(-1.0), 0.158655, 1e-6);
+    }
+
+    #[test]
+    fn test_normal_quantile() {
+        assert_close(normal_quantile(0.975), 1.959964, 1e-5);
+        assert_close(normal_quantile(0.5), 0.0, 1e-7);
+    }
+
+    #[test]
+    fn test_chi_squared_sf() {
+        assert_close(chi_squared_sf(3.841459, 1.0), 0.05, 1e-6);
+        assert_close(chi_squared_sf(5.991465, 2.0), 0.05, 1e-6);
+        assert_close(chi_squared_sf(1.0, 5.0), 0.962566, 1e-6);
+    }
+
+    #[test]
+    fn test_mann_whitney() {
+        let a = [19.0, 22.0, 16.0, 29.0, 24.0];
+        let b = [20.0, 11.0, 17.0, 12.0];
+        let test = mann_whitney(&a, &b);
+        assert_close(test.u, 17.0, 1e-9);
+        assert_close(test.p, 0.1113, 1e-4);
+
+        let same = mann_whitney(&[1.0, 1.0], &[1.0, 1.0]);
+        assert_close(same.p, 1.0, 1e-6);
+    }
+
+    #[test]
+    fn test_vargha_delaney() {
+        assert_close(vargha_delaney(&[1.0, 2.0], &[3.0, 4.0]), 1.0, 1e-9);
+        assert_close(vargha_delaney(&[1.0, 3.0], &[1.0, 2.0]), 0.375, 1e-9);
+    }
+
+    #[test]
+    fn test_friedman() {
+        let blocks = vec![
+            vec![1.0, 2.0, 3.0],
+            vec![1.0, 3.0, 2.0],
+            vec![1.0, 2.0, 3.0],
+            vec![2.0, 1.0, 3.0],
+        ];
+        let test = friedman(&blocks);
+        assert_eq!(test.average_ranks, vec![1.25, 2.0, 2.75]);
+        assert_close(test.chi_squared, 4.5, 1e-9);
+        assert_close(test.p, 0.105399, 1e-6);
+    }
+}
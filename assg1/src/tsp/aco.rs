@@ -0,0 +1,244 @@
+use super::TSP;
+use crate::log::Log;
+use crate::problem::{Problem, debug_validate};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Variant {
+    /// Every ant deposits pheromone in proportion to the quality of its tour.
+    AntSystem,
+    /// Only the best ant of the iteration deposits pheromone, which is kept
+    /// within computed bounds.
+    MaxMin,
+}
+
+pub struct AntColony<'a> {
+    problem: &'a TSP,
+    variant: Variant,
+    ants: usize,
+    iterations: usize,
+    alpha: f64,
+    beta: f64,
+    rho: f64,
+    two_opt: bool,
+    seed: Option<u64>,
+}
+
+/// Probability of constructing the best tour once the pheromone has converged,
+/// used to derive the lower pheromone bound of MAX-MIN Ant System.
+const P_BEST: f64 = 0.05;
+
+impl AntColony<'_> {
+    /// `alpha` and `beta` weigh the pheromone and the visibility of the edges,
+    /// and `rho` is the evaporation rate.
+    pub fn new(problem: &TSP, variant: Variant, ants: usize, iterations: usize,
+        alpha: f64, beta: f64, rho: f64) -> AntColony<'_>
+    {
+        assert!(ants > 0 && iterations > 0, "empty colony: {} ants, {} iterations", ants, iterations);
+        assert!(rho > 0.0 && rho <= 1.0, "invalid evaporation rate: {}", rho);
+        AntColony {
+            problem: problem,
+            variant: variant,
+            ants: ants,
+            iterations: iterations,
+            alpha: alpha,
+            beta: beta,
+            rho: rho,
+            two_opt: false,
+            seed: None,
+        }
+    }
+
+    /// Improves every constructed tour with 2-opt before it is logged and
+    /// deposits pheromone.
+    pub fn two_opt(mut self, two_opt: bool) -> Self {
+        self.two_opt = two_opt;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn run(&self, loggers: &mut Vec<&mut dyn Log<(Vec<usize>, u64)>>) {
+        self.colony(loggers);
+    }
+
+    /// Runs the colony and returns the final pheromone of every edge, as a
+    /// row-major matrix.
+    fn colony(&self, loggers: &mut Vec<&mut dyn Log<(Vec<usize>, u64)>>) -> Vec<f64> {
+        use quicli::prelude::*;
+        use std::time::Instant;
+
+        let start = Instant::now();
+        let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        info!("started the ant colony optimization with seed {}", seed);
+
+        let size = self.problem.dimension;
+        let visibility: Vec<f64> = (0..size * size)
+            .map(|i| (1.0 / (*self.problem.dm.get(i / size, i % size).unwrap()).max(1) as f64).powf(self.beta))
+            .collect();
+
+        let nearest_neighbor_length = self.problem.fitness(&super::naive::Greedy::new(self.problem).next(0)) as f64;
+        let (mut tau_min, mut tau_max) = self.bounds(nearest_neighbor_length);
+        let mut pheromone = vec![match self.variant {
+            Variant::AntSystem => self.ants as f64 / nearest_neighbor_length,
+            Variant::MaxMin => tau_max,
+        }; size * size];
+        let mut best_length = u64::MAX;
+        let local_search = if self.two_opt {
+            Some(super::local::LocalSearch::new(self.problem, super::local::CANDIDATES).depth(1).or_opt(false))
+        } else {
+            None
+        };
+
+        for iteration in 0..self.iterations {
+            let choice: Vec<f64> = pheromone.iter().zip(visibility.iter())
+                .map(|(tau, eta)| tau.powf(self.alpha) * eta)
+                .collect();
+
+            let mut tours = Vec::with_capacity(self.ants);
+            for _ in 0..self.ants {
+                let mut tour = self.construct(&choice, &mut rng);
+                if let Some(local_search) = &local_search {
+                    local_search.improve(&mut tour);
+                }
+                debug_validate(self.problem, &tour);
+                let length = self.problem.fitness(&tour);
+                for logger in loggers.iter_mut() {
+                    logger.log(&(tour.clone(), length));
+                }
+                tours.push((tour, length));
+            }
+
+            for tau in pheromone.iter_mut() {
+                *tau *= 1.0 - self.rho;
+            }
+            match self.variant {
+                Variant::AntSystem => {
+                    for (tour, length) in &tours {
+                        deposit(&mut pheromone, size, tour, 1.0 / *length as f64);
+                    }
+                },
+                Variant::MaxMin => {
+                    let (tour, length) = tours.iter().min_by_key(|(_, length)| *length).unwrap();
+                    if *length < best_length {
+                        best_length = *length;
+                        let bounds = self.bounds(best_length as f64);
+                        tau_min = bounds.0;
+                        tau_max = bounds.1;
+                    }
+                    deposit(&mut pheromone, size, tour, 1.0 / *length as f64);
+                    for tau in pheromone.iter_mut() {
+                        *tau = tau.max(tau_min).min(tau_max);
+                    }
+                }
+            }
+            info!("finished iteration #{}", iteration + 1);
+        }
+
+        let duration = start.elapsed();
+        info!("finished the ant colony optimization in {:?}", duration);
+        return pheromone;
+    }
+
+    /// Pheromone bounds of MAX-MIN Ant System for the best tour length so far.
+    fn bounds(&self, best_length: f64) -> (f64, f64) {
+        let tau_max = 1.0 / (self.rho * best_length);
+        let root = P_BEST.powf(1.0 / self.problem.dimension as f64);
+        let average_choices = (self.problem.dimension as f64 / 2.0).max(2.0);
+        let tau_min = tau_max * (1.0 - root) / ((average_choices - 1.0) * root);
+        (tau_min.min(tau_max), tau_max)
+    }
+
+    fn construct<R: Rng>(&self, choice: &[f64], rng: &mut R) -> Vec<usize> {
+        let size = self.problem.dimension;
+        let mut visited = vec![false; size];
+        let mut current = rng.gen_range(0, size);
+        let mut tour = Vec::with_capacity(size);
+        tour.push(current);
+        visited[current] = true;
+        for _ in 1..size {
+            let row = &choice[current * size..(current + 1) * size];
+            let total: f64 = row.iter().enumerate()
+                .filter(|(node, _)| !visited[*node])
+                .map(|(_, weight)| weight)
+                .sum();
+            let next = if total > 0.0 {
+                let mut threshold = rng.gen_range(0.0, total);
+                let mut chosen = None;
+                for (node, weight) in row.iter().enumerate() {
+                    if !visited[node] {
+                        chosen = Some(node);
+                        if threshold < *weight {
+                            break;
+                        }
+                        threshold -= weight;
+                    }
+                }
+                chosen.unwrap()
+            } else {
+                // every remaining edge has vanished, fall back to the closest node
+                (0..size)
+                    .filter(|node| !visited[*node])
+                    .min_by_key(|node| self.problem.dm.get(current, *node).unwrap())
+                    .unwrap()
+            };
+            tour.push(next);
+            visited[next] = true;
+            current = next;
+        }
+        return tour;
+    }
+}
+
+fn deposit(pheromone: &mut [f64], size: usize, tour: &[usize], amount: f64) {
+    for i in 0..tour.len() {
+        let (a, b) = (tour[i], tour[(i + 1) % tour.len()]);
+        pheromone[a * size + b] += amount;
+        pheromone[b * size + a] += amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::validate_permutation;
+    use crate::tsp::parser::parse_problem_instance;
+    use std::path::PathBuf;
+
+    impl Log<(Vec<usize>, u64)> for Vec<(Vec<usize>, u64)> {
+        fn log(&mut self, value: &(Vec<usize>, u64)) {
+            self.push(value.clone());
+        }
+    }
+
+    #[test]
+    fn test_colony() {
+        let problem = parse_problem_instance(&PathBuf::from("data/berlin11_modified.tsp")).unwrap();
+        for variant in [Variant::AntSystem, Variant::MaxMin].iter() {
+            let colony = AntColony::new(&problem, *variant, 5, 20, 1.0, 2.0, 0.1).seed(3);
+            let mut tours = Vec::new();
+            let pheromone = colony.colony(&mut vec![&mut tours]);
+
+            assert_eq!(tours.len(), 5 * 20);
+            for (tour, length) in &tours {
+                assert_eq!(validate_permutation(tour, problem.dimension), Ok(()));
+                assert_eq!(*length, problem.fitness(tour));
+            }
+            if let Variant::MaxMin = variant {
+                let best = tours.iter().map(|(_, length)| *length).min().unwrap();
+                let (tau_min, tau_max) = colony.bounds(best as f64);
+                assert!(pheromone.iter().all(|tau| *tau >= tau_min && *tau <= tau_max));
+            }
+
+            // the same seed gives the same tours and pheromone
+            let mut again = Vec::new();
+            assert_eq!(colony.colony(&mut vec![&mut again]), pheromone);
+            assert_eq!(again, tours);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use quicli::prelude::*;
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::{TSP, Distance};
+use crate::log::Log;
+use crate::problem::{Problem, debug_validate};
+
+/// A deterministic tour construction heuristic, started from a given node.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Heuristic {
+    /// Inserts the node closest to the tour at its cheapest position.
+    NearestInsertion,
+    /// Inserts the node farthest from the tour at its cheapest position.
+    FarthestInsertion,
+    /// Inserts the node that increases the length of the tour the least.
+    CheapestInsertion,
+    /// Clarke–Wright savings, with the starting node as the hub.
+    Savings,
+    /// Christofides, with a greedy instead of a minimum weight matching of the
+    /// odd nodes of the minimum spanning tree.
+    Christofides,
+}
+
+impl fmt::Display for Heuristic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Heuristic::NearestInsertion => "nearest-insertion",
+            Heuristic::FarthestInsertion => "farthest-insertion",
+            Heuristic::CheapestInsertion => "cheapest-insertion",
+            Heuristic::Savings => "savings",
+            Heuristic::Christofides => "christofides",
+        })
+    }
+}
+
+impl FromStr for Heuristic {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Heuristic, Error> {
+        match source {
+            "nearest-insertion" => Ok(Heuristic::NearestInsertion),
+            "farthest-insertion" => Ok(Heuristic::FarthestInsertion),
+            "cheapest-insertion" => Ok(Heuristic::CheapestInsertion),
+            "savings" => Ok(Heuristic::Savings),
+            "christofides" => Ok(Heuristic::Christofides),
+            _ => Err(format_err!("unknown heuristic {:?}", source))
+        }
+    }
+}
+
+/// Builds tours with a construction heuristic from `count` starting nodes
+/// spread evenly over the instance.
+pub struct Construction<'a, L = u64> {
+    problem: &'a TSP<L>,
+    heuristic: Heuristic,
+    count: usize,
+}
+
+impl<L: Distance> Construction<'_, L> {
+    pub fn new(problem: &TSP<L>, heuristic: Heuristic, count: usize) -> Construction<'_, L> {
+        Construction {
+            problem: problem,
+            heuristic: heuristic,
+            count: count,
+        }
+    }
+
+    pub fn run(&self, loggers: &mut Vec<&mut dyn Log<(Vec<usize>, L)>>) {
+        let count = self.count.min(self.problem.dimension);
+        for i in 0..count {
+            let solution = construct(self.problem, self.heuristic, i * self.problem.dimension / count);
+            debug_validate(self.problem, &solution);
+            let fitness = self.problem.fitness(&solution);
+            for logger in loggers.iter_mut() {
+                logger.log(&(solution.clone(), fitness));
+            }
+        }
+    }
+}
+
+pub fn construct<L>(problem: &TSP<L>, heuristic: Heuristic, starting_node: usize) -> Vec<usize> {
+    assert!(starting_node < problem.dimension, "invalid starting node: {}", starting_node);
+
+    if problem.dimension < 3 {
+        return (0..problem.dimension).map(|i| (starting_node + i) % problem.dimension).collect();
+    }
+    match heuristic {
+        Heuristic::NearestInsertion => insertion(problem, starting_node, false),
+        Heuristic::FarthestInsertion => insertion(problem, starting_node, true),
+        Heuristic::CheapestInsertion => cheapest_insertion(problem, starting_node),
+        Heuristic::Savings => savings(problem, starting_node),
+        Heuristic::Christofides => christofides(problem, starting_node),
+    }
+}
+
+/// Rounded distances may break the triangle inequality, so the differences of
+/// distances are signed.
+fn distance<L>(problem: &TSP<L>, a: usize, b: usize) -> i64 {
+    *problem.dm.get(a, b).unwrap() as i64
+}
+
+/// Follows the successors from the starting node.
+fn successors_to_tour(successors: &[usize], starting_node: usize) -> Vec<usize> {
+    let mut tour = Vec::with_capacity(successors.len());
+    let mut current = starting_node;
+    loop {
+        tour.push(current);
+        current = successors[current];
+        if current == starting_node {
+            break;
+        }
+    }
+    return tour;
+}
+
+fn insertion<L>(problem: &TSP<L>, starting_node: usize, farthest: bool) -> Vec<usize> {
+    let size = problem.dimension;
+    let mut successors = vec![usize::MAX; size];
+    successors[starting_node] = starting_node;
+    let mut in_tour = vec![starting_node];
+    // distance from every node to the closest node of the tour
+    let mut closest: Vec<i64> = (0..size).map(|node| distance(problem, starting_node, node)).collect();
+
+    for _ in 1..size {
+        let candidates = (0..size).filter(|node| successors[*node] == usize::MAX);
+        let node = if farthest {
+            candidates.max_by_key(|node| closest[*node]).unwrap()
+        } else {
+            candidates.min_by_key(|node| closest[*node]).unwrap()
+        };
+        let after = *in_tour.iter()
+            .min_by_key(|a| distance(problem, **a, node) + distance(problem, node, successors[**a])
+                - distance(problem, **a, successors[**a]))
+            .unwrap();
+        successors[node] = successors[after];
+        successors[after] = node;
+        in_tour.push(node);
+        for (other, closest) in closest.iter_mut().enumerate() {
+            *closest = (*closest).min(distance(problem, node, other));
+        }
+    }
+    return successors_to_tour(&successors, starting_node);
+}
+
+fn cheapest_insertion<L>(problem: &TSP<L>, starting_node: usize) -> Vec<usize> {
+    let size = problem.dimension;
+    let insertion_cost = |successors: &[usize], a: usize, node: usize| {
+        distance(problem, a, node) + distance(problem, node, successors[a]) - distance(problem, a, successors[a])
+    };
+
+    let mut successors = vec![usize::MAX; size];
+    successors[starting_node] = starting_node;
+    let mut in_tour = vec![starting_node];
+    // the cheapest insertion of every node, as the cost and the node to insert it after
+    let mut cheapest: Vec<(i64, usize)> = (0..size)
+        .map(|node| (2 * distance(problem, starting_node, node), starting_node))
+        .collect();
+
+    for _ in 1..size {
+        let node = (0..size)
+            .filter(|node| successors[*node] == usize::MAX)
+            .min_by_key(|node| cheapest[*node].0)
+            .unwrap();
+        let after = cheapest[node].1;
+        let before = successors[after];
+        successors[node] = before;
+        successors[after] = node;
+        in_tour.push(node);
+
+        for other in (0..size).filter(|other| successors[*other] == usize::MAX) {
+            if cheapest[other].1 == after {
+                // the edge of the cheapest insertion is gone, look through the whole tour
+                cheapest[other] = in_tour.iter()
+                    .map(|a| (insertion_cost(&successors, *a, other), *a))
+                    .min()
+                    .unwrap();
+            } else {
+                for a in [after, node].iter().cloned() {
+                    let cost = insertion_cost(&successors, a, other);
+                    if cost < cheapest[other].0 {
+                        cheapest[other] = (cost, a);
+                    }
+                }
+            }
+        }
+    }
+    return successors_to_tour(&successors, starting_node);
+}
+
+fn savings<L>(problem: &TSP<L>, hub: usize) -> Vec<usize> {
+    let size = problem.dimension;
+    let mut pairs: Vec<(i64, usize, usize)> = Vec::with_capacity((size - 1) * (size - 2) / 2);
+    for i in (0..size).filter(|i| *i != hub) {
+        for j in (i + 1..size).filter(|j| *j != hub) {
+            let saving = distance(problem, hub, i) + distance(problem, hub, j) - distance(problem, i, j);
+            pairs.push((saving, i, j));
+        }
+    }
+    pairs.sort_by(|a, b| b.0.cmp(&a.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+
+    // merge the routes hub-i-hub into a single path, never closing a cycle
+    let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); size];
+    let mut components: Vec<usize> = (0..size).collect();
+    fn find(components: &mut Vec<usize>, node: usize) -> usize {
+        let mut root = node;
+        while components[root] != root {
+            root = components[root];
+        }
+        let mut node = node;
+        while components[node] != root {
+            let next = components[node];
+            components[node] = root;
+            node = next;
+        }
+        return root;
+    }
+    let mut merges = 0;
+    for (_, i, j) in pairs {
+        if merges == size - 2 {
+            break;
+        }
+        if neighbors[i].len() < 2 && neighbors[j].len() < 2 {
+            let (root_i, root_j) = (find(&mut components, i), find(&mut components, j));
+            if root_i != root_j {
+                components[root_i] = root_j;
+                neighbors[i].push(j);
+                neighbors[j].push(i);
+                merges += 1;
+            }
+        }
+    }
+
+    let mut tour = vec![hub];
+    let mut previous = hub;
+    let mut current = (0..size).find(|node| *node != hub && neighbors[*node].len() < 2).unwrap();
+    loop {
+        tour.push(current);
+        match neighbors[current].iter().find(|node| **node != previous) {
+            Some(next) if tour.len() < size => {
+                previous = current;
+                current = *next;
+            },
+            _ => break,
+        }
+    }
+    return tour;
+}
+
+fn christofides<L>(problem: &TSP<L>, starting_node: usize) -> Vec<usize> {
+    let size = problem.dimension;
+
+    // Prim's minimum spanning tree
+    let mut edges: Vec<(usize, usize)> = Vec::with_capacity(size + size / 2);
+    let mut in_tree = vec![false; size];
+    let mut closest: Vec<(i64, usize)> = (0..size)
+        .map(|node| (distance(problem, starting_node, node), starting_node))
+        .collect();
+    in_tree[starting_node] = true;
+    for _ in 1..size {
+        let node = (0..size)
+            .filter(|node| !in_tree[*node])
+            .min_by_key(|node| closest[*node].0)
+            .unwrap();
+        in_tree[node] = true;
+        edges.push((closest[node].1, node));
+        for other in (0..size).filter(|other| !in_tree[*other]) {
+            let distance = distance(problem, node, other);
+            if distance < closest[other].0 {
+                closest[other] = (distance, node);
+            }
+        }
+    }
+
+    // greedy matching of the nodes of odd degree
+    let mut degrees = vec![0; size];
+    for (a, b) in &edges {
+        degrees[*a] += 1;
+        degrees[*b] += 1;
+    }
+    let odd: Vec<usize> = (0..size).filter(|node| degrees[*node] % 2 == 1).collect();
+    let mut pairs: Vec<(i64, usize, usize)> = Vec::with_capacity(odd.len() * odd.len() / 2);
+    for (i, a) in odd.iter().enumerate() {
+        for b in &odd[i + 1..] {
+            pairs.push((distance(problem, *a, *b), *a, *b));
+        }
+    }
+    pairs.sort();
+    let mut matched = vec![false; size];
+    for (_, a, b) in pairs {
+        if !matched[a] && !matched[b] {
+            matched[a] = true;
+            matched[b] = true;
+            edges.push((a, b));
+        }
+    }
+
+    // Hierholzer's Eulerian circuit, shortcut to the first visit of every node
+    let mut incident: Vec<Vec<usize>> = vec![Vec::new(); size];
+    for (i, (a, b)) in edges.iter().enumerate() {
+        incident[*a].push(i);
+        incident[*b].push(i);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut next_edge = vec![0; size];
+    let mut stack = vec![starting_node];
+    let mut visited = vec![false; size];
+    let mut tour = Vec::with_capacity(size);
+    while let Some(node) = stack.last().cloned() {
+        while next_edge[node] < incident[node].len() && used[incident[node][next_edge[node]]] {
+            next_edge[node] += 1;
+        }
+        if next_edge[node] < incident[node].len() {
+            let edge = incident[node][next_edge[node]];
+            used[edge] = true;
+            let (a, b) = edges[edge];
+            stack.push(if a == node { b } else { a });
+        } else {
+            stack.pop();
+            if !visited[node] {
+                visited[node] = true;
+                tour.push(node);
+            }
+        }
+    }
+    // the circuit comes out reversed, which does not change the tour
+    let start = tour.iter().position(|node| *node == starting_node).unwrap();
+    tour.rotate_left(start);
+    return tour;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tsp::parser::parse_problem_instance;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_construct() {
+        let problem = parse_problem_instance(&PathBuf::from("data/berlin52.tsp")).unwrap();
+        let heuristics = [
+            Heuristic::NearestInsertion, Heuristic::FarthestInsertion, Heuristic::CheapestInsertion,
+            Heuristic::Savings, Heuristic::Christofides,
+        ];
+        for heuristic in heuristics.iter() {
+            for starting_node in [0, 17, 51].iter() {
+                let tour = construct(&problem, *heuristic, *starting_node);
+                let mut sorted = tour.clone();
+                sorted.sort();
+                assert_eq!(sorted, (0..52).collect::<Vec<usize>>(), "{} from {}", heuristic, starting_node);
+                assert_eq!(tour[0], *starting_node);
+                // within 30% of the optimum of 7542
+                assert!(problem.fitness(&tour) < 9800, "{} from {}", heuristic, starting_node);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_heuristic() {
+        assert_eq!("cheapest-insertion".parse::<Heuristic>().unwrap(), Heuristic::CheapestInsertion);
+        assert_eq!(Heuristic::Christofides.to_string().parse::<Heuristic>().unwrap(), Heuristic::Christofides);
+        assert!("greedy-matching".parse::<Heuristic>().is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use quicli::prelude::*;
+use rand::Rng;
+use std::str::FromStr;
+
+use super::CoordinateSystem;
+
+/// The way the nodes of a generated instance are laid out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Layout {
+    /// Nodes spread uniformly over the square.
+    Uniform,
+    /// Nodes gathered around the given number of uniformly spread centers.
+    Clustered(usize),
+    /// Nodes on a regular grid, filled row by row.
+    Grid,
+    /// Geographical nodes spread uniformly over the latitudes and longitudes.
+    Geographical,
+}
+
+impl FromStr for Layout {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Layout, Error> {
+        let mut parts = source.splitn(2, ':');
+        match (parts.next().unwrap(), parts.next()) {
+            ("uniform", None) => Ok(Layout::Uniform),
+            ("clustered", None) => Ok(Layout::Clustered(10)),
+            ("clustered", Some(clusters)) => match clusters.parse::<usize>()? {
+                0 => Err(format_err!("a clustered layout needs at least one cluster")),
+                clusters => Ok(Layout::Clustered(clusters))
+            },
+            ("grid", None) => Ok(Layout::Grid),
+            ("geo", None) => Ok(Layout::Geographical),
+            _ => Err(format_err!("unknown layout {:?}", source))
+        }
+    }
+}
+
+impl Layout {
+    pub fn coord_system(&self) -> CoordinateSystem {
+        match self {
+            Layout::Geographical => CoordinateSystem::Geographical,
+            _ => CoordinateSystem::Euclidean,
+        }
+    }
+}
+
+/// Side of the square the Euclidean nodes are placed in.
+const SIDE: f64 = 10000.0;
+
+pub fn generate_coordinates<R: Rng>(layout: Layout, dimension: usize, rng: &mut R) -> Vec<(f64, f64)> {
+    match layout {
+        Layout::Uniform => (0..dimension)
+            .map(|_| (rng.gen_range(0.0, SIDE).round(), rng.gen_range(0.0, SIDE).round()))
+            .collect(),
+        Layout::Clustered(clusters) => {
+            assert!(clusters > 0, "invalid number of clusters: {}", clusters);
+            let centers: Vec<(f64, f64)> = (0..clusters)
+                .map(|_| (rng.gen_range(0.0, SIDE), rng.gen_range(0.0, SIDE)))
+                .collect();
+            let spread = SIDE / (4.0 * (clusters as f64).sqrt());
+            (0..dimension)
+                .map(|_| {
+                    let (center_x, center_y) = centers[rng.gen_range(0, clusters)];
+                    // Box-Muller transform
+                    let radius = spread * (-2.0 * (1.0 - rng.gen::<f64>()).ln()).sqrt();
+                    let angle = 2.0 * std::f64::consts::PI * rng.gen::<f64>();
+                    (
+                        (center_x + radius * angle.cos()).clamp(0.0, SIDE).round(),
+                        (center_y + radius * angle.sin()).clamp(0.0, SIDE).round(),
+                    )
+                })
+                .collect()
+        },
+        Layout::Grid => {
+            let columns = (dimension as f64).sqrt().ceil() as usize;
+            let spacing = SIDE / columns.max(1) as f64;
+            (0..dimension)
+                .map(|i| (((i % columns) as f64 * spacing).round(), ((i / columns) as f64 * spacing).round()))
+                .collect()
+        },
+        Layout::Geographical => {
+            // DDD.MM, where the fractional part holds the minutes, drawn as
+            // a whole number of minutes so that the sign covers both parts
+            fn degrees<R: Rng>(rng: &mut R, max_degrees: i32) -> f64 {
+                let total = rng.gen_range(-max_degrees * 60, max_degrees * 60 + 1);
+                let (degrees, minutes) = (total.abs() / 60, total.abs() % 60);
+                total.signum() as f64 * (degrees * 100 + minutes) as f64 / 100.0
+            }
+            (0..dimension)
+                .map(|_| (degrees(rng, 90), degrees(rng, 180)))
+                .collect()
+        }
+    }
+}
+
+/// Formats the nodes as a TSPLIB file that `parse_problem_instance` reads back.
+pub fn format_instance(name: &str, comment: &str, coord_system: &CoordinateSystem,
+    coordinates: &[(f64, f64)]) -> String
+{
+    let mut output = format!(
+        "NAME: {}\nTYPE: TSP\nCOMMENT: {}\nDIMENSION: {}\nEDGE_WEIGHT_TYPE: {}\nNODE_COORD_SECTION\n",
+        name, comment, coordinates.len(), match coord_system {
+            CoordinateSystem::Euclidean => "EUC_2D",
+            CoordinateSystem::Geographical => "GEO",
+        }
+    );
+    for (i, (x, y)) in coordinates.iter().enumerate() {
+        match coord_system {
+            CoordinateSystem::Euclidean => output.push_str(&format!("{} {} {}\n", i + 1, x, y)),
+            CoordinateSystem::Geographical => output.push_str(&format!("{} {:.2} {:.2}\n", i + 1, x, y)),
+        }
+    }
+    output.push_str("EOF\n");
+    return output;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn round_trip(layout: Layout) {
+        use crate::tsp::parser::parse_problem_instance;
+
+        let coordinates = generate_coordinates(layout, 50, &mut ChaCha8Rng::seed_from_u64(0));
+        let source = format_instance("test", "round trip", &layout.coord_system(), &coordinates);
+        let path = std::env::temp_dir().join(format!("assg1-round-trip-{:?}.tsp", layout));
+        write_to_file(&path, &source).unwrap();
+        let problem = parse_problem_instance(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(problem.name.as_deref(), Some("test"));
+        assert_eq!(problem.dimension(), 50);
+        assert_eq!(problem.coordinates(), &coordinates);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(Layout::Uniform);
+        round_trip(Layout::Clustered(3));
+        round_trip(Layout::Grid);
+        round_trip(Layout::Geographical);
+    }
+
+    #[test]
+    fn test_geographical_range() {
+        use crate::tsp::parser::parse_problem_instance;
+
+        let coordinates = generate_coordinates(Layout::Geographical, 1000, &mut ChaCha8Rng::seed_from_u64(0));
+        let source = format_instance("geo", "range", &CoordinateSystem::Geographical, &coordinates);
+        let path = std::env::temp_dir().join("assg1-geographical-range.tsp");
+        write_to_file(&path, &source).unwrap();
+        let problem = parse_problem_instance(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // DDD.MM to degrees, keeping the sign on both parts
+        let to_degrees = |coordinate: f64| {
+            let hundredths = (coordinate.abs() * 100.0).round() as i64;
+            assert!(hundredths % 100 < 60, "invalid minutes in {}", coordinate);
+            coordinate.signum() * ((hundredths / 100) as f64 + (hundredths % 100) as f64 / 60.0)
+        };
+        for (latitude, longitude) in problem.coordinates() {
+            assert!(to_degrees(*latitude).abs() <= 90.0, "invalid latitude {}", latitude);
+            assert!(to_degrees(*longitude).abs() <= 180.0, "invalid longitude {}", longitude);
+        }
+        // the minutes of the nodes within a degree of the equator are kept
+        assert!(problem.coordinates().iter().any(|(latitude, _)| latitude.abs() < 1.0 && *latitude != 0.0));
+    }
+
+    #[test]
+    fn test_seeded_generation() {
+        let a = generate_coordinates(Layout::Uniform, 20, &mut ChaCha8Rng::seed_from_u64(7));
+        let b = generate_coordinates(Layout::Uniform, 20, &mut ChaCha8Rng::seed_from_u64(7));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        assert_eq!("clustered:4".parse::<Layout>().unwrap(), Layout::Clustered(4));
+        assert_eq!("geo".parse::<Layout>().unwrap(), Layout::Geographical);
+        assert!("spiral".parse::<Layout>().is_err());
+        assert!("clustered:0".parse::<Layout>().is_err());
+    }
+}
@@ -0,0 +1,369 @@
+use super::{TSP, Distance};
+use crate::log::Log;
+use crate::problem::{Problem, debug_validate};
+use rand::Rng;
+use std::collections::VecDeque;
+
+/// Lin–Kernighan local search, chaining 2-opt moves into a single k-opt move,
+/// combined with Or-opt moves, i.e. the 3-opt moves that relocate a segment of
+/// up to three nodes.
+///
+/// Only the moves introducing an edge to one of the nearest neighbors of a node
+/// are considered, and the nodes whose surroundings have not changed since
+/// they last failed to improve the tour are skipped (don't-look bits).
+pub struct LocalSearch<'a, L = u64> {
+    problem: &'a TSP<L>,
+    neighbors: Vec<Vec<usize>>,
+    depth: usize,
+    or_opt: bool,
+}
+
+/// The length of the longest segment relocated by Or-opt.
+const MAX_SEGMENT: usize = 3;
+
+/// The default number of nearest neighbors in the candidate lists.
+pub const CANDIDATES: usize = 10;
+
+/// The default number of 2-opt moves chained into a Lin–Kernighan move.
+pub const DEPTH: usize = 10;
+
+impl<L: Distance> LocalSearch<'_, L> {
+    /// Builds the candidate lists of the `candidates` nearest neighbors of
+    /// every node.
+    pub fn new(problem: &TSP<L>, candidates: usize) -> LocalSearch<'_, L> {
+        LocalSearch {
+            problem: problem,
+            neighbors: problem.neighbors(candidates),
+            depth: DEPTH,
+            or_opt: true,
+        }
+    }
+
+    /// Sets the largest number of 2-opt moves chained into a Lin–Kernighan
+    /// move, where 1 leaves plain 2-opt moves.
+    pub fn depth(mut self, depth: usize) -> Self {
+        assert!(depth > 0, "invalid depth: {}", depth);
+        self.depth = depth;
+        self
+    }
+
+    /// Enables or disables the Or-opt moves, leaving only the Lin–Kernighan
+    /// ones.
+    pub fn or_opt(mut self, or_opt: bool) -> Self {
+        self.or_opt = or_opt;
+        self
+    }
+
+    /// Improves random tours `count` times, logging every local optimum.
+    pub fn run<R: Rng>(&self, count: usize, rng: &mut R, loggers: &mut Vec<&mut dyn Log<(Vec<usize>, L)>>) {
+        use rand::seq::SliceRandom;
+
+        for _ in 0..count {
+            let mut solution: Vec<usize> = (0..self.problem.dimension).collect();
+            solution.shuffle(rng);
+            self.improve(&mut solution);
+            debug_validate(self.problem, &solution);
+            let fitness = self.problem.fitness(&solution);
+            for logger in loggers.iter_mut() {
+                logger.log(&(solution.clone(), fitness));
+            }
+        }
+    }
+
+    /// Applies improving moves until the tour is a local optimum. Returns
+    /// whether the tour has changed.
+    pub fn improve(&self, tour: &mut Vec<usize>) -> bool {
+        let size = tour.len();
+        if size < 5 {
+            return false;
+        }
+        let mut position = vec![0; size];
+        for (i, node) in tour.iter().enumerate() {
+            position[*node] = i;
+        }
+        let mut queue: VecDeque<usize> = tour.iter().cloned().collect();
+        let mut queued = vec![true; size];
+        let mut changed = false;
+        while let Some(node) = queue.pop_front() {
+            queued[node] = false;
+            let touched = match self.lin_kernighan_move(node, tour, &mut position) {
+                Some(touched) => Some(touched),
+                None if self.or_opt => self.or_opt_move(node, tour, &mut position),
+                None => None,
+            };
+            if let Some(touched) = touched {
+                changed = true;
+                for node in touched {
+                    if !queued[node] {
+                        queued[node] = true;
+                        queue.push_back(node);
+                    }
+                }
+            }
+        }
+        return changed;
+    }
+
+    fn distance(&self, a: usize, b: usize) -> u64 {
+        *self.problem.dm.get(a, b).unwrap() as u64
+    }
+
+    /// Removes the edge between `t1` and its successor (or predecessor) `t2`,
+    /// and chains 2-opt moves replacing the edge closing the tour at `t1`.
+    /// Every move adds an edge from the last `t2` to one of its neighbors
+    /// `t3`, removes an edge from `t3` to `t4`, and closes the tour with an
+    /// edge between `t4` and `t1`, which the next move removes in turn. The
+    /// tour is left at the best point of the chain, so a single move may
+    /// replace up to `depth + 1` edges.
+    ///
+    /// Every neighbor of `t2` is tried as the first `t3`, and then the one
+    /// with the largest gain is followed.
+    fn lin_kernighan_move(&self, t1: usize, tour: &mut Vec<usize>, position: &mut Vec<usize>) -> Option<Vec<usize>> {
+        let size = tour.len();
+        for t2 in [tour[(position[t1] + 1) % size], tour[(position[t1] + size - 1) % size]].iter().cloned() {
+            let gain = self.distance(t1, t2) as i64;
+            for t3 in self.neighbors[t2].iter().cloned() {
+                if self.distance(t2, t3) as i64 >= gain {
+                    break;
+                }
+                if let Some(touched) = self.chain(t1, t2, t3, gain, tour, position) {
+                    return Some(touched);
+                }
+            }
+        }
+        None
+    }
+
+    /// Follows the chain of 2-opt moves from the first `t3`, returning the
+    /// nodes whose edges have changed if the tour is shorter at its best
+    /// point, and leaving the tour as it was otherwise.
+    fn chain(&self, t1: usize, t2: usize, t3: usize, gain: i64,
+        tour: &mut Vec<usize>, position: &mut Vec<usize>) -> Option<Vec<usize>>
+    {
+        let size = tour.len();
+        // the node following `t3` on the way back to `t2`, whose edge to `t3`
+        // is removed, or `None` when the move is not allowed
+        let t4_of = |t2: usize, t3: usize, tour: &Vec<usize>, position: &Vec<usize>, added: &Vec<(usize, usize)>| {
+            let forward = tour[(position[t1] + 1) % size] == t2;
+            let t4 = if forward {
+                tour[(position[t3] + size - 1) % size]
+            } else {
+                tour[(position[t3] + 1) % size]
+            };
+            if t3 == t1 || t3 == t2 || t4 == t2 || added.contains(&(t3.min(t4), t3.max(t4))) {
+                None
+            } else {
+                Some(t4)
+            }
+        };
+
+        let (mut t2, mut t3, mut gain) = (t2, t3, gain);
+        let mut t4 = t4_of(t2, t3, tour, position, &Vec::new())?;
+        // the reversed paths, undone past the best point
+        let mut reversals: Vec<(usize, usize)> = Vec::new();
+        // the edges added along the chain, which are never removed again
+        let mut added: Vec<(usize, usize)> = Vec::new();
+        let mut touched = vec![t1];
+        let (mut best_gain, mut best_length) = (0, 0);
+        loop {
+            gain += self.distance(t3, t4) as i64 - self.distance(t2, t3) as i64;
+            let reversal = if tour[(position[t1] + 1) % size] == t2 {
+                (position[t2], position[t4])
+            } else {
+                (position[t4], position[t2])
+            };
+            reverse(tour, position, reversal.0, reversal.1);
+            reversals.push(reversal);
+            added.push((t2.min(t3), t2.max(t3)));
+            touched.extend_from_slice(&[t2, t3, t4]);
+            let closed = gain - self.distance(t4, t1) as i64;
+            if closed > best_gain {
+                best_gain = closed;
+                best_length = reversals.len();
+            }
+            if reversals.len() == self.depth {
+                break;
+            }
+
+            t2 = t4;
+            let next = self.neighbors[t2].iter().cloned()
+                .take_while(|t3| (self.distance(t2, *t3) as i64) < gain)
+                .filter_map(|t3| t4_of(t2, t3, tour, position, &added).map(|t4| (t3, t4)))
+                .max_by_key(|(t3, t4)| self.distance(*t3, *t4) as i64 - self.distance(t2, *t3) as i64);
+            match next {
+                Some((next_t3, next_t4)) => {
+                    t3 = next_t3;
+                    t4 = next_t4;
+                },
+                None => break,
+            }
+        }
+        while reversals.len() > best_length {
+            let (from, to) = reversals.pop().unwrap();
+            reverse(tour, position, from, to);
+        }
+        if best_length > 0 {
+            Some(touched)
+        } else {
+            None
+        }
+    }
+
+    /// Moves a segment of up to three nodes starting at `a` between a neighbor
+    /// of one of its ends and the successor or predecessor of that neighbor.
+    fn or_opt_move(&self, a: usize, tour: &mut Vec<usize>, position: &mut Vec<usize>) -> Option<Vec<usize>> {
+        let size = tour.len();
+        if size < 2 * MAX_SEGMENT + 2 {
+            return None;
+        }
+        let at = |offset: usize, tour: &Vec<usize>| tour[(position[a] + offset) % size];
+        let in_segment = |node: usize, length: usize| (position[node] + size - position[a]) % size < length;
+
+        for length in 1..=MAX_SEGMENT {
+            let first = a;
+            let last = at(length - 1, tour);
+            let before = at(size - 1, tour);
+            let after = at(length, tour);
+            let removal_gain = (self.distance(before, first) + self.distance(last, after)) as i64
+                - self.distance(before, after) as i64;
+            if removal_gain <= 0 {
+                continue;
+            }
+            for (end, other_end) in [(first, last), (last, first)].iter().cloned() {
+                for c in self.neighbors[end].iter().cloned() {
+                    let end_c = self.distance(end, c) as i64;
+                    if end_c >= removal_gain {
+                        break;
+                    }
+                    if in_segment(c, length) {
+                        continue;
+                    }
+                    let c_position = position[c];
+                    for e in [tour[(c_position + 1) % size], tour[(c_position + size - 1) % size]].iter().cloned() {
+                        if in_segment(e, length) {
+                            continue;
+                        }
+                        let insertion_cost = end_c + self.distance(e, other_end) as i64 - self.distance(c, e) as i64;
+                        if insertion_cost < removal_gain {
+                            let c_before_e = tour[(c_position + 1) % size] == e;
+                            relocate(tour, position, a, length, c, end == first, c_before_e);
+                            return Some(vec![before, after, first, last, c, e]);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Reverses the path going forward from position `from` to position `to`,
+/// reversing the rest of the tour instead if it is shorter.
+fn reverse(tour: &mut Vec<usize>, position: &mut Vec<usize>, from: usize, to: usize) {
+    let size = tour.len();
+    let mut length = (to + size - from) % size + 1;
+    let (mut from, mut to) = (from, to);
+    if 2 * length > size {
+        from = (to + 1) % size;
+        to = (from + size - 1 + size - length) % size;
+        length = size - length;
+    }
+    for k in 0..length / 2 {
+        let p = (from + k) % size;
+        let q = (to + size - k) % size;
+        tour.swap(p, q);
+        position[tour[p]] = p;
+        position[tour[q]] = q;
+    }
+}
+
+/// Moves the segment of `length` nodes starting at `start` next to `c`, with
+/// `first_next_to_c` telling which end of the segment ends up adjacent to `c`,
+/// and `after_c` telling on which side of `c` it is inserted.
+fn relocate(tour: &mut Vec<usize>, position: &mut Vec<usize>, start: usize, length: usize, c: usize,
+    first_next_to_c: bool, after_c: bool)
+{
+    tour.rotate_left(position[start]);
+    let mut segment: Vec<usize> = tour.drain(0..length).collect();
+    // the end adjacent to c comes first when inserting after c and last otherwise
+    if first_next_to_c != after_c {
+        segment.reverse();
+    }
+    let c_index = tour.iter().position(|node| *node == c).unwrap();
+    let index = if after_c { c_index + 1 } else { c_index };
+    let tail = tour.split_off(index);
+    tour.extend(segment);
+    tour.extend(tail);
+    for (i, node) in tour.iter().enumerate() {
+        position[*node] = i;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::validate_permutation;
+    use crate::tsp::parser::parse_problem_instance;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_improve() {
+        let problem = parse_problem_instance(&PathBuf::from("data/kroA100.tsp")).unwrap();
+        for or_opt in [false, true].iter() {
+            let local_search = LocalSearch::new(&problem, 10).or_opt(*or_opt);
+            let mut tour: Vec<usize> = (0..100).collect();
+            let before = problem.fitness(&tour);
+            assert!(local_search.improve(&mut tour));
+            assert_eq!(validate_permutation(&tour, 100), Ok(()));
+            assert!(problem.fitness(&tour) < before);
+            // a local optimum stays put
+            assert!(!local_search.improve(&mut tour));
+        }
+    }
+
+    #[test]
+    fn test_lin_kernighan() {
+        use rand::SeedableRng;
+        use rand::seq::SliceRandom;
+
+        let problem = parse_problem_instance(&PathBuf::from("data/kroA100.tsp")).unwrap();
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(0);
+        let tours: Vec<Vec<usize>> = (0..10).map(|_| {
+            let mut tour: Vec<usize> = (0..100).collect();
+            tour.shuffle(&mut rng);
+            tour
+        }).collect();
+        let total = |depth: usize| tours.iter().map(|tour| {
+            let local_search = LocalSearch::new(&problem, 10).depth(depth).or_opt(false);
+            let mut tour = tour.clone();
+            let before = problem.fitness(&tour);
+            assert!(local_search.improve(&mut tour));
+            assert_eq!(validate_permutation(&tour, 100), Ok(()));
+            assert!(problem.fitness(&tour) < before);
+            problem.fitness(&tour)
+        }).sum::<u64>();
+        // the chained moves escape the 2-opt local optima
+        assert!(total(DEPTH) < total(1));
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut tour: Vec<usize> = (0..6).collect();
+        let mut position: Vec<usize> = (0..6).collect();
+        reverse(&mut tour, &mut position, 4, 1);
+        // the complement 2..=3 is reversed instead of 4, 5, 0, 1
+        assert_eq!(tour, vec![0, 1, 3, 2, 4, 5]);
+        assert_eq!(position, vec![0, 1, 3, 2, 4, 5]);
+    }
+
+    #[test]
+    fn test_relocate() {
+        let mut tour: Vec<usize> = (0..8).collect();
+        let mut position: Vec<usize> = (0..8).collect();
+        relocate(&mut tour, &mut position, 1, 2, 5, true, true);
+        assert_eq!(tour, vec![3, 4, 5, 1, 2, 6, 7, 0]);
+        for (i, node) in tour.iter().enumerate() {
+            assert_eq!(position[*node], i);
+        }
+    }
+}
@@ -1,46 +1,84 @@
 use crate::log::Log;
+use crate::problem::Length;
+use quicli::prelude::*;
+use serde::{Serialize, Deserialize};
+
+/// The smaller of two lengths, which are never NaN.
+fn min<L: Length>(a: L, b: L) -> L {
+    if b < a { b } else { a }
+}
+
+/// The greater of two lengths, which are never NaN.
+fn max<L: Length>(a: L, b: L) -> L {
+    if b > a { b } else { a }
+}
 
-pub struct Discoverer {
-    currents: Option<(u32, u32)>,
-    bests: Vec<u32>,
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "L: Length")]
+pub struct Discoverer<L = u64> {
+    currents: Option<(L, L)>,
+    bests: Vec<L>,
+    worst: Option<L>,
+    best: Option<L>,
     pub best_solution: Vec<usize>,
 }
 
-impl Log<(Vec<usize>, u32)> for Discoverer {
-    fn log(&mut self, value: &(Vec<usize>, u32)) {
+impl<L: Length> Log<(Vec<usize>, L)> for Discoverer<L> {
+    // `Option::is_none_or` needs Rust 1.82
+    #[allow(clippy::unnecessary_map_or)]
+    fn log(&mut self, value: &(Vec<usize>, L)) {
         let (solution, measure) = value;
-        match &self.currents {
-            Some((current_best, current_worst)) => {
-                let best = if measure < current_best { self.best_solution = solution.clone(); measure } else { current_best };
-                let worst = if measure > current_worst { measure } else { current_worst };
-                self.currents = Some((*best, *worst));
-            },
-            None => self.currents = Some((*measure, *measure))
+        if self.best.map_or(true, |best| *measure < best) {
+            self.best = Some(*measure);
+            self.best_solution = solution.clone();
         }
+        self.currents = Some(match self.currents {
+            Some((current_best, current_worst)) => (min(*measure, current_best), max(*measure, current_worst)),
+            None => (*measure, *measure)
+        });
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap()
+    }
+
+    fn restore(&mut self, state: serde_json::Value) -> Result<(), Error> {
+        *self = serde_json::from_value(state)?;
+        Ok(())
     }
 }
 
-impl Discoverer {
-    pub fn new() -> Discoverer {
+impl<L: Length> Discoverer<L> {
+    pub fn new() -> Discoverer<L> {
         Discoverer {
             currents: None,
             bests: Vec::new(),
+            worst: None,
+            best: None,
             best_solution: Vec::new(),
         }
     }
 
+    /// Closes the current run, so that every run contributes its own best.
+    /// A run which logged nothing contributes nothing.
     pub fn carry(&mut self) {
-        let (best, _) = self.currents.unwrap();
-        self.bests.push(best);
+        if let Some((best, worst)) = self.currents.take() {
+            self.bests.push(best);
+            self.worst = Some(self.worst.map_or(worst, |current_worst| max(current_worst, worst)));
+        }
+    }
+
+    pub fn bests(&self) -> &Vec<L> {
+        &self.bests
     }
 
     pub fn print(&self) {
         let count = self.bests.len();
-        let best: u32 = *self.bests.iter().min().unwrap();
-        let worst: u32 = self.currents.unwrap().1;
-        let avg: f64 = self.bests.iter().sum::<u32>() as f64 / count as f64;
+        let best: L = self.bests.iter().cloned().fold(self.bests[0], min);
+        let worst: L = self.worst.unwrap();
+        let avg: f64 = self.bests.iter().map(|value| value.to_f64()).sum::<f64>() / count as f64;
         let variance = self.bests.iter().map(|value| {
-            let diff = avg - (*value as f64);
+            let diff = avg - value.to_f64();
             diff * diff
         }).sum::<f64>() / count as f64;
         let std = variance.sqrt();
@@ -49,28 +87,32 @@ impl Discoverer {
     }
 }
 
-pub struct Cohorter {
+impl<L: Length> Default for Discoverer<L> {
+    fn default() -> Discoverer<L> {
+        Discoverer::new()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "L: Length")]
+pub struct Cohorter<L = u64> {
     logged: usize,
     generation_size: usize,
     generations: usize,
-    currents: Option<(u32, u32)>,
-    current_bests: Vec<u32>,
-    current_worsts: Vec<u32>,
-    accumulated_bests: Vec<Vec<u32>>,
-    worsts: Option<Vec<u32>>,
+    currents: Option<(L, L)>,
+    current_bests: Vec<L>,
+    current_worsts: Vec<L>,
+    accumulated_bests: Vec<Vec<L>>,
+    worsts: Option<Vec<L>>,
 }
 
-impl Log<(Vec<usize>, u32)> for Cohorter {
-    fn log(&mut self, value: &(Vec<usize>, u32)) {
+impl<L: Length> Log<(Vec<usize>, L)> for Cohorter<L> {
+    fn log(&mut self, value: &(Vec<usize>, L)) {
         let (_, measure) = value;
-        match &self.currents {
-            Some((current_best, current_worst)) => {
-                let best = if measure < current_best { measure } else { current_best };
-                let worst = if measure > current_worst { measure } else { current_worst };
-                self.currents = Some((*best, *worst));
-            },
-            None => self.currents = Some((*measure, *measure))
-        }
+        self.currents = Some(match self.currents {
+            Some((current_best, current_worst)) => (min(*measure, current_best), max(*measure, current_worst)),
+            None => (*measure, *measure)
+        });
         self.logged += 1;
         if self.logged == self.generation_size {
             let (current_best, current_worst) = self.currents.unwrap();
@@ -80,13 +122,21 @@ impl Log<(Vec<usize>, u32)> for Cohorter {
             self.currents = None;
         }
     }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap()
+    }
+
+    fn restore(&mut self, state: serde_json::Value) -> Result<(), Error> {
+        *self = serde_json::from_value(state)?;
+        Ok(())
+    }
 }
 
-use quicli::prelude::*;
 use std::path::PathBuf;
 
-impl Cohorter {
-    pub fn new(generation_size: usize, generations: usize) -> Cohorter {
+impl<L: Length> Cohorter<L> {
+    pub fn new(generation_size: usize, generations: usize) -> Cohorter<L> {
         Cohorter {
             logged: 0,
             generation_size: generation_size,
@@ -107,13 +157,8 @@ impl Cohorter {
         self.worsts = match &self.worsts {
             Some(worsts) => {
                 let worsts = Some(worsts.iter().zip(self.current_worsts.iter())
-                    .map(|(worst, current_worst)| {
-                        if current_worst > worst {
-                            *current_worst
-                        } else {
-                            *worst
-                        }
-                    }).collect());
+                    .map(|(worst, current_worst)| max(*worst, *current_worst))
+                    .collect());
                 self.current_worsts.clear();
                 worsts
             },
@@ -121,15 +166,23 @@ impl Cohorter {
         };
     }
 
-    pub fn dump(&self, path: &PathBuf) -> Result<(), Error> {
-        let bests: Vec<u32> = (0..self.generations).map(|i| self.accumulated_bests.iter()
-                .min_by(|a, b| a[i].cmp(&b[i])).unwrap()[i])
+    /// The best, average and worst fitness in every generation across the
+    /// carried runs.
+    pub fn curves(&self) -> (Vec<L>, Vec<f64>, Vec<L>) {
+        let bests: Vec<L> = (0..self.generations).map(|i| self.accumulated_bests.iter()
+                .map(|accumulated_best| accumulated_best[i])
+                .fold(self.accumulated_bests[0][i], min))
             .collect();
         let avgs: Vec<f64> = (0..self.generations).map(|i| self.accumulated_bests.iter()
-                .map(|accumulated_best| accumulated_best[i])
-                .sum::<u32>() as f64 / self.accumulated_bests.len() as f64)
+                .map(|accumulated_best| accumulated_best[i].to_f64())
+                .sum::<f64>() / self.accumulated_bests.len() as f64)
             .collect();
-        let worsts: &Vec<u32> = &self.worsts.as_ref().unwrap();
+        let worsts: Vec<L> = self.worsts.clone().unwrap();
+        (bests, avgs, worsts)
+    }
+
+    pub fn dump(&self, path: &PathBuf) -> Result<(), Error> {
+        let (bests, avgs, worsts) = self.curves();
         let output = bests.iter().zip(avgs.iter()).zip(worsts.iter())
             .enumerate()
             .map(|(i, ((best, avg), worst))| {
@@ -139,6 +192,202 @@ impl Cohorter {
         .collect::<Vec<String>>()
         .join("\n");
 
-        write_to_file(&path, &output)
+        write_to_file(path, &output)
+    }
+}
+
+use std::time::Instant;
+
+/// An improvement of the best fitness of a run.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "L: Length")]
+pub struct Improvement<L = u64> {
+    pub evaluations: usize,
+    pub seconds: f64,
+    pub best: L,
+}
+
+/// Records the best fitness of every run against the number of evaluations
+/// and the wall time, rather than generations, so that the algorithms
+/// without generations can be compared on the same axes.
+///
+/// Every logged solution counts as one evaluation, whatever it cost to find:
+/// an offspring of the evolutionary algorithm, a tour of an ant, a local
+/// optimum or a construction. The unit names what was counted, so that the
+/// dumps of different algorithms are not mistaken for each other.
+///
+/// The wall time of a run is measured from the creation of the logger or the
+/// end of the previous run.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "L: Length")]
+pub struct Anytime<L = u64> {
+    unit: String,
+    evaluations: usize,
+    /// Seconds the current run had taken when it was checkpointed.
+    elapsed: f64,
+    #[serde(skip)]
+    start: Option<Instant>,
+    current: Vec<Improvement<L>>,
+    runs: Vec<Vec<Improvement<L>>>,
+}
+
+impl<L: Length> Log<(Vec<usize>, L)> for Anytime<L> {
+    // `Option::is_none_or` needs Rust 1.82
+    #[allow(clippy::unnecessary_map_or)]
+    fn log(&mut self, value: &(Vec<usize>, L)) {
+        let (_, measure) = value;
+        self.evaluations += 1;
+        if self.current.last().map_or(true, |improvement| *measure < improvement.best) {
+            self.current.push(Improvement {
+                evaluations: self.evaluations,
+                seconds: self.seconds(),
+                best: *measure,
+            });
+        }
+    }
+
+    fn save(&self) -> serde_json::Value {
+        let mut state = serde_json::to_value(self).unwrap();
+        state["elapsed"] = serde_json::json!(self.seconds());
+        state
+    }
+
+    fn restore(&mut self, state: serde_json::Value) -> Result<(), Error> {
+        *self = serde_json::from_value(state)?;
+        self.start = Some(Instant::now());
+        Ok(())
+    }
+}
+
+impl<L: Length> Anytime<L> {
+    /// Creates a logger counting the evaluations in the given unit, e.g.
+    /// `"tours"`.
+    pub fn new(unit: &str) -> Anytime<L> {
+        Anytime {
+            unit: unit.to_string(),
+            evaluations: 0,
+            elapsed: 0.0,
+            start: Some(Instant::now()),
+            current: Vec::new(),
+            runs: Vec::new(),
+        }
+    }
+
+    fn seconds(&self) -> f64 {
+        self.elapsed + self.start.map_or(0.0, |start| start.elapsed().as_secs_f64())
+    }
+
+    pub fn carry(&mut self) {
+        self.runs.push(self.current.drain(0..).collect());
+        self.evaluations = 0;
+        self.elapsed = 0.0;
+        self.start = Some(Instant::now());
+    }
+
+    /// The first improvement of every carried run reaching the target, or
+    /// `None` for the runs that never reached it.
+    pub fn hits(&self, target: L) -> Vec<Option<&Improvement<L>>> {
+        self.runs.iter()
+            .map(|run| run.iter().find(|improvement| improvement.best <= target))
+            .collect()
+    }
+
+    /// Dumps every improvement as `run;evaluations;seconds;best`, i.e. the
+    /// anytime curve of every run as a step function, under a header naming
+    /// the unit of the evaluations.
+    pub fn dump(&self, path: &PathBuf) -> Result<(), Error> {
+        let header = format!("run;{};seconds;best", self.unit);
+        let output = std::iter::once(header)
+            .chain(self.runs.iter().enumerate()
+                .flat_map(|(run, improvements)| improvements.iter().map(move |improvement| format!("{};{};{};{}",
+                    run, improvement.evaluations, improvement.seconds, improvement.best))))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        write_to_file(path, &output)
+    }
+
+    /// Dumps the empirical run-time distribution of reaching the target as
+    /// `seconds;evaluations;probability` under the same kind of header as
+    /// [`dump`](#method.dump), one line per run reaching it. The
+    /// seconds and the evaluations are sorted on their own, and the
+    /// probability of the `i`-th line is `(i - 1/2) / runs`, as in
+    /// time-to-target plots, so that the runs missing the target keep the
+    /// distribution below one.
+    pub fn dump_ttt(&self, target: L, path: &PathBuf) -> Result<(), Error> {
+        let hits: Vec<&Improvement<L>> = self.hits(target).into_iter().flatten().collect();
+        let mut seconds: Vec<f64> = hits.iter().map(|hit| hit.seconds).collect();
+        seconds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mut evaluations: Vec<usize> = hits.iter().map(|hit| hit.evaluations).collect();
+        evaluations.sort_unstable();
+        info!("{} of {} runs reached the target {}", hits.len(), self.runs.len(), target);
+
+        let header = format!("seconds;{};probability", self.unit);
+        let output = std::iter::once(header)
+            .chain(seconds.iter().zip(evaluations.iter()).enumerate()
+                .map(|(i, (seconds, evaluations))| format!("{};{};{}",
+                    seconds, evaluations, (i as f64 + 0.5) / self.runs.len() as f64)))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        write_to_file(path, &output)
+    }
+}
+
+impl<L: Length> Default for Anytime<L> {
+    fn default() -> Anytime<L> {
+        Anytime::new("evaluations")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discoverer() {
+        let mut discoverer = Discoverer::new();
+        for measure in [10, 12, 8].iter() {
+            discoverer.log(&(Vec::new(), *measure));
+        }
+        discoverer.carry();
+        // a run which logged nothing
+        discoverer.carry();
+        discoverer.log(&(Vec::new(), 9));
+        discoverer.carry();
+        assert_eq!(discoverer.bests(), &vec![8, 9]);
+        assert_eq!(discoverer.worst, Some(12));
+    }
+
+    #[test]
+    fn test_anytime() {
+        let mut anytime = Anytime::new("evaluations");
+        for measure in [10, 12, 8, 8, 5].iter() {
+            anytime.log(&(Vec::new(), *measure));
+        }
+        anytime.carry();
+        for measure in [9, 7].iter() {
+            anytime.log(&(Vec::new(), *measure));
+        }
+        anytime.carry();
+
+        let evaluations = |run: &Vec<Improvement>| run.iter()
+            .map(|improvement| (improvement.evaluations, improvement.best))
+            .collect::<Vec<(usize, u64)>>();
+        assert_eq!(evaluations(&anytime.runs[0]), vec![(1, 10), (3, 8), (5, 5)]);
+        assert_eq!(evaluations(&anytime.runs[1]), vec![(1, 9), (2, 7)]);
+        let hits = |target: u64| anytime.hits(target).iter()
+            .map(|hit| hit.map(|improvement| improvement.evaluations))
+            .collect::<Vec<Option<usize>>>();
+        assert_eq!(hits(8), vec![Some(3), Some(2)]);
+        assert_eq!(hits(6), vec![Some(5), None]);
+
+        let restored = {
+            let mut restored: Anytime = Anytime::new("tours");
+            restored.restore(anytime.save()).unwrap();
+            restored
+        };
+        assert_eq!(restored.runs.len(), 2);
+        assert_eq!(restored.unit, "evaluations");
     }
 }
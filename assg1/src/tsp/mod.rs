@@ -1,32 +1,173 @@
-mod dm;
+pub mod aco;
+pub mod construct;
+pub(crate) mod dm;
+pub mod generator;
+pub mod local;
 pub mod logs;
 pub mod naive;
 pub mod ops;
+pub mod optima;
 pub mod parser;
+pub mod render;
 
 use dm::DistanceMatrix;
 
-pub struct TSP {
+pub enum CoordinateSystem {
+    Euclidean,
+    Geographical,
+}
+
+use std::fmt;
+
+impl fmt::Debug for CoordinateSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match &self {
+            Self::Euclidean => "Euclidean2D",
+            Self::Geographical => "Geographical",
+        })
+    }
+}
+
+/// A TSP instance whose tour lengths are summed in `L`: `u64` sums the
+/// distances of the distance matrix, and `f64` the exact distances between
+/// the coordinates.
+pub struct TSP<L = u64> {
+    #[allow(dead_code)]
     pub name: Option<String>,
     dimension: usize,
     dm: DistanceMatrix,
+    /// The number of decimals the distances keep, in units of `10^-decimals`.
+    decimals: u32,
+    coord_system: CoordinateSystem,
+    coordinates: Vec<(f64, f64)>,
+    length: PhantomData<L>,
 }
 
-use crate::problem::Problem;
+use std::marker::PhantomData;
 
-impl Problem for TSP {
-    type Solution = Vec<usize>;
-    type Measure = u32;
+impl<L> TSP<L> {
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
 
-    fn fitness(&self, solution: &Vec<usize>) -> u32 {
-        use std::u32;
+    pub fn coord_system(&self) -> &CoordinateSystem {
+        &self.coord_system
+    }
+
+    /// The node coordinates exactly as they appear in the node coord section.
+    pub fn coordinates(&self) -> &Vec<(f64, f64)> {
+        &self.coordinates
+    }
 
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    /// The length of the tour with the distances rounded the way TSPLIB does,
+    /// whatever the number of decimals the instance was parsed with.
+    pub fn rounded_length(&self, tour: &[usize]) -> u64 {
+        use parser::{exact_distance, round_distance};
+
+        (0..tour.len())
+            .map(|i| {
+                let (a, b) = (&self.coordinates[tour[i]], &self.coordinates[tour[(i + 1) % tour.len()]]);
+                round_distance(&self.coord_system, exact_distance(&self.coord_system, a, b), 0) as u64
+            })
+            .sum()
+    }
+
+    /// The same instance with the tour lengths summed in another type.
+    pub fn measured<M>(self) -> TSP<M> {
+        TSP {
+            name: self.name,
+            dimension: self.dimension,
+            dm: self.dm,
+            decimals: self.decimals,
+            coord_system: self.coord_system,
+            coordinates: self.coordinates,
+            length: PhantomData,
+        }
+    }
+
+    /// The `candidates` nearest neighbors of every node, closest first, with
+    /// ties broken by the lower index.
+    pub fn neighbors(&self, candidates: usize) -> Vec<Vec<usize>> {
+        let candidates = candidates.min(self.dimension.saturating_sub(1));
+        (0..self.dimension)
+            .map(|a| {
+                let key = |b: &usize| (*self.dm.get(a, *b).unwrap(), *b);
+                let mut others: Vec<usize> = (0..self.dimension).filter(|b| *b != a).collect();
+                if candidates < others.len() {
+                    others.select_nth_unstable_by_key(candidates, key);
+                    others.truncate(candidates);
+                }
+                others.sort_unstable_by_key(key);
+                others
+            })
+            .collect()
+    }
+}
+
+use crate::problem::{Problem, Permutation, Length, validate_permutation};
+use quicli::prelude::Error;
+
+/// A type the distances between the nodes, and so the tour lengths, are
+/// summed in.
+pub trait Distance: Length + std::iter::Sum {
+    /// Whether the distances are exact rather than those of TSPLIB, whose
+    /// known optima then do not apply.
+    const EXACT: bool;
+
+    /// The distance between two nodes, or `None` when one is out of range.
+    fn distance(problem: &TSP<Self>, a: usize, b: usize) -> Option<Self>;
+}
+
+/// The distances of the distance matrix, rounded the way TSPLIB does or kept
+/// with decimals. The sum of 32-bit distances cannot overflow 64 bits.
+impl Distance for u64 {
+    const EXACT: bool = false;
+
+    fn distance(problem: &TSP<u64>, a: usize, b: usize) -> Option<u64> {
+        problem.dm.get(a, b).map(|distance| *distance as u64)
+    }
+}
+
+/// The exact distances, without the rounding of TSPLIB.
+impl Distance for f64 {
+    const EXACT: bool = true;
+
+    fn distance(problem: &TSP<f64>, a: usize, b: usize) -> Option<f64> {
+        let (a, b) = (problem.coordinates.get(a)?, problem.coordinates.get(b)?);
+        Some(parser::exact_distance(&problem.coord_system, a, b))
+    }
+}
+
+impl<L: Distance> Problem for TSP<L> {
+    type Solution = Vec<usize>;
+    type Measure = L;
+
+    /// The length of the closed walk through the solution, or
+    /// `Length::INFEASIBLE` when it is shorter than the dimension or visits a
+    /// node out of range. A solution visiting a node twice still has a finite
+    /// length, so use `validate` to check that it is a tour.
+    fn fitness(&self, solution: &Vec<usize>) -> L {
         (0..self.dimension)
             .zip((0..self.dimension).cycle().skip(1))
             .map(|(i, j)| solution.get(i)
                 .and_then(|a| solution.get(j)
-                    .and_then(|b| self.dm.get(*a, *b))))
-            .sum::<Option<u32>>()
-            .unwrap_or(u32::MAX)
+                    .and_then(|b| L::distance(self, *a, *b))))
+            .sum::<Option<L>>()
+            .unwrap_or(L::INFEASIBLE)
+    }
+    fn validate(&self, solution: &Vec<usize>) -> Result<(), Error> {
+        Ok(validate_permutation(solution, self.dimension)?)
+    }
+}
+
+impl<L: Distance> Permutation for TSP<L> {
+    type Length = L;
+
+    fn size(&self) -> usize {
+        self.dimension
     }
 }
@@ -1,25 +1,26 @@
-use super::TSP;
+use super::{TSP, Distance};
 
-pub struct Random<'a> {
-    problem: &'a TSP,
+pub struct Random<'a, L = u64> {
+    problem: &'a TSP<L>,
     count: usize,
 }
     
 use crate::log::Log;
-use crate::problem::Problem;
+use crate::problem::{Problem, debug_validate};
 
 #[allow(dead_code)]
-impl Random<'_> {
-    pub fn new(problem: &TSP, count: usize) -> Random {
+impl<L: Distance> Random<'_, L> {
+    pub fn new(problem: &TSP<L>, count: usize) -> Random<'_, L> {
         Random {
             problem: problem,
             count: count,
         }
     }
 
-    pub fn run(&self, loggers: &mut Vec<&mut dyn Log<(Vec<usize>, u32)>>) {
+    pub fn run(&self, loggers: &mut Vec<&mut dyn Log<(Vec<usize>, L)>>) {
         for _ in 0..self.count {
             let solution = self.next();
+            debug_validate(self.problem, &solution);
             let fitness = self.problem.fitness(&solution);
             for logger in loggers.iter_mut() {
                 logger.log(&(solution.clone(), fitness));
@@ -36,21 +37,30 @@ impl Random<'_> {
     }
 }
 
-pub struct Greedy<'a> {
-    problem: &'a TSP,
+/// Nearest neighbor construction, looking for the nearest unvisited node in the
+/// candidate list first and through all the nodes only when every candidate
+/// has been visited.
+pub struct Greedy<'a, L = u64> {
+    problem: &'a TSP<L>,
+    neighbors: Vec<Vec<usize>>,
 }
 
+/// Number of nearest neighbors in the candidate lists.
+const CANDIDATES: usize = 16;
+
 #[allow(dead_code)]
-impl Greedy<'_> {
-    pub fn new(problem: &TSP) -> Greedy {
+impl<L: Distance> Greedy<'_, L> {
+    pub fn new(problem: &TSP<L>) -> Greedy<'_, L> {
         Greedy {
             problem: problem,
+            neighbors: problem.neighbors(CANDIDATES),
         }
     }
 
-    pub fn run(&self, loggers: &mut Vec<&mut dyn Log<(Vec<usize>, u32)>>) {
+    pub fn run(&self, loggers: &mut Vec<&mut dyn Log<(Vec<usize>, L)>>) {
         for starting_node in 0..self.problem.dimension {
             let solution = self.next(starting_node);
+            debug_validate(self.problem, &solution);
             let fitness = self.problem.fitness(&solution);
             for logger in loggers.iter_mut() {
                 logger.log(&(solution.clone(), fitness));
@@ -58,20 +68,61 @@ impl Greedy<'_> {
         }
     }
 
-    fn next(&self, starting_node: usize) -> Vec<usize> {
+    /// Ties between equally distant nodes go to the lower index.
+    pub(super) fn next(&self, starting_node: usize) -> Vec<usize> {
         assert!(starting_node < self.problem.dimension, "invalid starting node: {}", starting_node);
 
-        let mut genotype: Vec<usize> = vec![starting_node];
+        let mut visited = vec![false; self.problem.dimension];
+        let mut genotype: Vec<usize> = Vec::with_capacity(self.problem.dimension);
         let mut current_node: usize = starting_node;
+        visited[starting_node] = true;
+        genotype.push(starting_node);
         for _ in 0..self.problem.dimension - 1 {
-            let nearest_node: usize = self.problem.dm.get_adjacent(current_node)
-                .iter()
-                .filter(|(i, _)| !genotype.contains(i))
-                .min_by(|a, b| a.1.cmp(b.1))
-                .unwrap().0;
+            let nearest_node = match self.neighbors[current_node].iter().find(|node| !visited[**node]) {
+                Some(node) => *node,
+                None => (0..self.problem.dimension)
+                    .filter(|node| !visited[*node])
+                    .min_by_key(|node| self.problem.dm.get(current_node, *node).unwrap())
+                    .unwrap(),
+            };
+            visited[nearest_node] = true;
+            genotype.push(nearest_node);
+            current_node = nearest_node;
+        }
+        return genotype;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tsp::parser::parse_problem_instance;
+    use std::path::PathBuf;
+
+    /// The original construction, scanning all the nodes for every node.
+    fn reference(problem: &TSP, starting_node: usize) -> Vec<usize> {
+        let mut genotype: Vec<usize> = vec![starting_node];
+        let mut current_node: usize = starting_node;
+        for _ in 0..problem.dimension - 1 {
+            let nearest_node: usize = (0..problem.dimension)
+                .filter(|i| !genotype.contains(i))
+                .min_by_key(|i| problem.dm.get(current_node, *i).unwrap())
+                .unwrap();
             genotype.push(nearest_node);
             current_node = nearest_node;
         }
         return genotype;
     }
+
+    #[test]
+    fn test_greedy_matches_reference() {
+        for instance in ["data/berlin52.tsp", "data/kroA200.tsp", "data/ali535.tsp"].iter() {
+            let problem = parse_problem_instance(&PathBuf::from(instance)).unwrap();
+            let greedy = Greedy::new(&problem);
+            for starting_node in (0..problem.dimension).step_by(problem.dimension / 3) {
+                assert_eq!(greedy.next(starting_node), reference(&problem, starting_node),
+                    "{} from {}", instance, starting_node);
+            }
+        }
+    }
 }
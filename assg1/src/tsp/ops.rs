@@ -1,33 +1,34 @@
 #[allow(dead_code)]
 pub mod initialize {
     use crate::ea::{Individual, Initialize};
-    use super::super::TSP;
+    use rand::Rng;
+    use crate::problem::Permutation;
+    use super::super::{TSP, Distance, construct, local, naive};
 
-    pub struct Random<'a> {
-        problem: &'a TSP,
+    pub struct Random<'a, P: Permutation = TSP> {
+        problem: &'a P,
     }
 
-    impl Random<'_> {
-        pub fn new<'a>(problem: &'a TSP) -> Random<'a> {
+    impl<P: Permutation> Random<'_, P> {
+        pub fn new<'a>(problem: &'a P) -> Random<'a, P> {
             Random {
                 problem: problem,
             }
         }
     }
 
-    impl Initialize for Random<'_> {
-        type Problem = TSP;
+    impl<P: Permutation> Initialize for Random<'_, P> {
+        type Problem = P;
 
-        fn initialize(&self, pop_size: usize) -> Vec<Individual<TSP>> {
+        fn initialize<R: Rng>(&self, pop_size: usize, rng: &mut R) -> Vec<Individual<P>> {
             use rand::seq::SliceRandom;
-            use crate::problem::Problem;
 
-            let mut population: Vec<Individual<TSP>> = Vec::new();
+            let mut population: Vec<Individual<P>> = Vec::new();
             for _ in 0..pop_size {
-                let mut genotype: Vec<usize> = (0..self.problem.dimension).collect();
-                genotype.shuffle(&mut rand::thread_rng());
+                let mut genotype: Vec<usize> = (0..self.problem.size()).collect();
+                genotype.shuffle(rng);
 
-                let individual = Individual::<TSP> {
+                let individual = Individual::<P> {
                     fitness: self.problem.fitness(&genotype),
                     genotype: genotype,
                 };
@@ -37,53 +38,64 @@ pub mod initialize {
         }
     }
 
-    pub struct Greedy<'a> {
-        problem: &'a TSP,
+    /// Builds a percentage of the individuals with the nearest neighbor
+    /// construction, and the rest at random.
+    pub struct Greedy<'a, L = u64> {
+        problem: &'a TSP<L>,
         percentage: f64,
+        greedy: naive::Greedy<'a, L>,
+        distinct_starts: bool,
     }
 
-    impl Greedy<'_> {
-        pub fn new<'a>(problem: &'a TSP, percentage: f64) -> Greedy<'a> {
+    impl<L: Distance> Greedy<'_, L> {
+        pub fn new<'a>(problem: &'a TSP<L>, percentage: f64) -> Greedy<'a, L> {
             Greedy {
                 problem: problem,
                 percentage: percentage,
+                greedy: naive::Greedy::new(problem),
+                distinct_starts: false,
             }
         }
+
+        /// Starts every greedy individual from a different node, as long as
+        /// there are enough nodes, instead of from a random one.
+        pub fn distinct_starts(mut self, distinct_starts: bool) -> Self {
+            self.distinct_starts = distinct_starts;
+            self
+        }
     }
 
-    impl Initialize for Greedy<'_> {
-        type Problem = TSP;
+    impl<L: Distance> Initialize for Greedy<'_, L> {
+        type Problem = TSP<L>;
 
-        fn initialize(&self, pop_size: usize) -> Vec<Individual<TSP>> {
+        fn initialize<R: Rng>(&self, pop_size: usize, rng: &mut R) -> Vec<Individual<TSP<L>>> {
             use rand::distributions::{Distribution, Uniform};
             use rand::seq::SliceRandom;
             use crate::problem::Problem;
 
             let greedy_individuals = (self.percentage * pop_size as f64) as usize;
-            let mut population: Vec<Individual<TSP>> = Vec::new();
+            let starting_nodes: Option<Vec<usize>> = if self.distinct_starts {
+                let mut starting_nodes: Vec<usize> = (0..self.problem.dimension).collect();
+                starting_nodes.shuffle(rng);
+                Some(starting_nodes)
+            } else {
+                None
+            };
+            let mut population: Vec<Individual<TSP<L>>> = Vec::new();
             for i in 0..pop_size {
                 let genotype = if i < greedy_individuals {
-                    let distribution = Uniform::from(0..self.problem.dimension);
-                    let starting_node = distribution.sample(&mut rand::thread_rng());
-                    let mut genotype: Vec<usize> = vec![starting_node];
-                    let mut current_node: usize = starting_node;
-                    for _ in 0..self.problem.dimension - 1 {
-                        let nearest_node: usize = self.problem.dm.get_adjacent(current_node)
-                            .iter()
-                            .filter(|(i, _)| !genotype.contains(i))
-                            .min_by(|a, b| a.1.cmp(b.1))
-                            .unwrap().0;
-                        genotype.push(nearest_node);
-                        current_node = nearest_node;
-                    }
-                    genotype
+                    let starting_node = match &starting_nodes {
+                        Some(starting_nodes) => starting_nodes[i % starting_nodes.len()],
+                        None => Uniform::from(0..self.problem.dimension).sample(rng),
+                    };
+                    self.greedy.next(starting_node)
                 } else {
                     let mut genotype: Vec<usize> = (0..self.problem.dimension).collect();
-                    genotype.shuffle(&mut rand::thread_rng());
+                    genotype.shuffle(rng);
                     genotype
                 };
 
-                let individual = Individual::<TSP> {
+                let individual = Individual::<TSP<L>> {
                     fitness: self.problem.fitness(&genotype),
                     genotype: genotype,
                 };
@@ -92,61 +104,137 @@ pub mod initialize {
             return population;
         }
     }
+
+    /// Builds a percentage of the individuals with a construction heuristic
+    /// started from random nodes, and the rest at random.
+    pub struct Construction<'a, L = u64> {
+        problem: &'a TSP<L>,
+        heuristic: construct::Heuristic,
+        percentage: f64,
+    }
+
+    impl<L: Distance> Construction<'_, L> {
+        pub fn new<'a>(problem: &'a TSP<L>, heuristic: construct::Heuristic, percentage: f64) -> Construction<'a, L> {
+            Construction {
+                problem: problem,
+                heuristic: heuristic,
+                percentage: percentage,
+            }
+        }
+    }
+
+    impl<L: Distance> Initialize for Construction<'_, L> {
+        type Problem = TSP<L>;
+
+        fn initialize<R: Rng>(&self, pop_size: usize, rng: &mut R) -> Vec<Individual<TSP<L>>> {
+            use crate::problem::Problem;
+
+            let constructed_individuals = (self.percentage * pop_size as f64) as usize;
+            let mut population = Random::new(self.problem).initialize(pop_size, rng);
+            for individual in population.iter_mut().take(constructed_individuals) {
+                let starting_node = rng.gen_range(0, self.problem.dimension);
+                individual.genotype = construct::construct(self.problem, self.heuristic, starting_node);
+                individual.fitness = self.problem.fitness(&individual.genotype);
+            }
+            return population;
+        }
+    }
+
+    /// Improves a percentage of the random individuals with Lin–Kernighan and
+    /// Or-opt.
+    pub struct LocalSearch<'a, L = u64> {
+        problem: &'a TSP<L>,
+        percentage: f64,
+        local_search: local::LocalSearch<'a, L>,
+    }
+
+    impl<L: Distance> LocalSearch<'_, L> {
+        pub fn new<'a>(problem: &'a TSP<L>, percentage: f64) -> LocalSearch<'a, L> {
+            LocalSearch {
+                problem: problem,
+                percentage: percentage,
+                local_search: local::LocalSearch::new(problem, local::CANDIDATES),
+            }
+        }
+    }
+
+    impl<L: Distance> Initialize for LocalSearch<'_, L> {
+        type Problem = TSP<L>;
+
+        fn initialize<R: Rng>(&self, pop_size: usize, rng: &mut R) -> Vec<Individual<TSP<L>>> {
+            use crate::problem::Problem;
+
+            let improved_individuals = (self.percentage * pop_size as f64) as usize;
+            let mut population = Random::new(self.problem).initialize(pop_size, rng);
+            for individual in population.iter_mut().take(improved_individuals) {
+                self.local_search.improve(&mut individual.genotype);
+                individual.fitness = self.problem.fitness(&individual.genotype);
+            }
+            return population;
+        }
+    }
 }
 
 #[allow(dead_code)]
 pub mod select {
     use crate::ea::{Individual, Select};
+    use crate::problem::{Length, Permutation};
+    use rand::Rng;
+    use std::marker::PhantomData;
     use super::super::TSP;
 
-    pub struct Tournament {
+    pub struct Tournament<P: Permutation = TSP> {
         tour_size: usize,
+        problem: PhantomData<P>,
     }
 
-    impl Tournament {
-        pub fn new(tour_size: usize) -> Tournament {
+    impl<P: Permutation> Tournament<P> {
+        pub fn new(tour_size: usize) -> Tournament<P> {
             Tournament {
                 tour_size: tour_size,
+                problem: PhantomData,
             }
         }
     }
 
-    impl Select for Tournament {
-        type Problem = TSP;
+    impl<P: Permutation> Select for Tournament<P> {
+        type Problem = P;
 
-        fn select<'a>(&self, population: &'a Vec<Individual<TSP>>) -> &'a Individual<TSP> {
+        fn select<'a, R: Rng>(&self, population: &'a Vec<Individual<P>>, rng: &mut R) -> &'a Individual<P> {
             use rand::seq::SliceRandom;
 
-            let mut tournament: Vec<&Individual<TSP>> = Vec::new();
+            let mut tournament: Vec<&Individual<P>> = Vec::new();
             for _ in 0..self.tour_size {
-                tournament.push(population.choose(&mut rand::thread_rng()).unwrap());
+                tournament.push(population.choose(rng).unwrap());
             }
-            return tournament.iter().min_by(|a, b| a.fitness.cmp(&b.fitness)).unwrap();
+            return tournament.iter().min_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap()).unwrap();
         }
     }
 
-    pub struct RouletteWheel {
+    pub struct RouletteWheel<P: Permutation = TSP> {
         beta: f64,
+        problem: PhantomData<P>,
     }
 
-    impl RouletteWheel {
-        pub fn new(beta: f64) -> RouletteWheel {
+    impl<P: Permutation> RouletteWheel<P> {
+        pub fn new(beta: f64) -> RouletteWheel<P> {
             RouletteWheel {
                 beta: beta,
+                problem: PhantomData,
             }
         }
     }
 
-    impl Select for RouletteWheel {
-        type Problem = TSP;
+    impl<P: Permutation> Select for RouletteWheel<P> {
+        type Problem = P;
 
-        fn select<'a>(&self, population: &'a Vec<Individual<TSP>>) -> &'a Individual<TSP> {
+        fn select<'a, R: Rng>(&self, population: &'a Vec<Individual<P>>, rng: &mut R) -> &'a Individual<P> {
             use rand::distributions::{Distribution, WeightedIndex};
             
             let distribution = WeightedIndex::new(
-                population.iter().map(|individual| (-self.beta * individual.fitness as f64).exp())
+                population.iter().map(|individual| (-self.beta * individual.fitness.to_f64()).exp())
             ).unwrap();
-            return population.get(distribution.sample(&mut rand::thread_rng())).unwrap();
+            return population.get(distribution.sample(rng)).unwrap();
         }
     }
 }
@@ -154,15 +242,17 @@ pub mod select {
 #[allow(dead_code)]
 pub mod crossover {
     use crate::ea::{Individual, Crossover};
+    use crate::problem::Permutation;
+    use rand::Rng;
     use super::super::TSP;
 
-    pub struct OX<'a> {
-        problem: &'a TSP,
+    pub struct OX<'a, P: Permutation = TSP> {
+        problem: &'a P,
         probability: f64,
     }
 
-    impl OX<'_> {
-        pub fn new<'a>(problem: &'a TSP, probability: f64) -> OX<'a> {
+    impl<P: Permutation> OX<'_, P> {
+        pub fn new<'a>(problem: &'a P, probability: f64) -> OX<'a, P> {
             OX {
                 problem: problem,
                 probability: probability,
@@ -170,23 +260,20 @@ pub mod crossover {
         }
     }
 
-    impl Crossover for OX<'_> {
-        type Problem = TSP;
+    impl<P: Permutation> Crossover for OX<'_, P> {
+        type Problem = P;
 
-        fn crossover<'a>(&self, a: &'a Individual<TSP>, b: &'a Individual<TSP>)
-            -> Individual<TSP>
+        fn crossover<'a, R: Rng>(&self, a: &'a Individual<P>, b: &'a Individual<P>, rng: &mut R)
+            -> Individual<P>
         {
-            use rand::Rng;
-            use crate::problem::Problem;
-
             assert_eq!(a.genotype.len(), b.genotype.len(), "mismatched genotype lengths");
 
-            if rand::thread_rng().gen_range(0.0, 1.0) < self.probability {
+            if rng.gen_range(0.0, 1.0) < self.probability {
                 use rand::distributions::{Distribution, Uniform};
 
                 let distribution = Uniform::from(0..a.genotype.len());
-                let first = distribution.sample(&mut rand::thread_rng());
-                let second = distribution.sample(&mut rand::thread_rng());
+                let first = distribution.sample(rng);
+                let second = distribution.sample(rng);
 
                 let (lower, greater) = if first > second {
                     (second, first)
@@ -196,7 +283,7 @@ pub mod crossover {
 
                 let subsequence = &a.genotype[lower..=greater];
                 let mut genotype = b.genotype.clone();
-                genotype.retain(|x| !subsequence.contains(&x));
+                genotype.retain(|x| !subsequence.contains(x));
 
                 let mut tail = genotype.split_off(lower);
                 genotype.extend_from_slice(subsequence);
@@ -212,13 +299,13 @@ pub mod crossover {
         }
     }
 
-    pub struct CX<'a> {
-        problem: &'a TSP,
+    pub struct CX<'a, P: Permutation = TSP> {
+        problem: &'a P,
         probability: f64,
     }
 
-    impl CX<'_> {
-        pub fn new<'a>(problem: &'a TSP, probability: f64) -> CX<'a> {
+    impl<P: Permutation> CX<'_, P> {
+        pub fn new<'a>(problem: &'a P, probability: f64) -> CX<'a, P> {
             CX {
                 problem: problem,
                 probability: probability,
@@ -226,18 +313,15 @@ pub mod crossover {
         }
     }
 
-    impl Crossover for CX<'_> {
-        type Problem = TSP;
+    impl<P: Permutation> Crossover for CX<'_, P> {
+        type Problem = P;
 
-        fn crossover<'a>(&self, a: &'a Individual<TSP>, b: &'a Individual<TSP>)
-            -> Individual<TSP>
+        fn crossover<'a, R: Rng>(&self, a: &'a Individual<P>, b: &'a Individual<P>, rng: &mut R)
+            -> Individual<P>
         {
-            use rand::Rng;
-            use crate::problem::Problem;
-
             assert_eq!(a.genotype.len(), b.genotype.len(), "mismatched genotype lengths");
 
-            if rand::thread_rng().gen_range(0.0, 1.0) < self.probability {
+            if rng.gen_range(0.0, 1.0) < self.probability {
                 let mut cycle = Vec::new();
                 let first = *a.genotype.first().unwrap();
 
@@ -255,7 +339,7 @@ pub mod crossover {
 
                 let genotype = a.genotype.iter().zip(b.genotype.iter())
                     .map(|(i, j)| -> usize {
-                        if cycle.contains(&i) { *i } else { *j }
+                        if cycle.contains(i) { *i } else { *j }
                     })
                     .collect();
 
@@ -273,15 +357,17 @@ pub mod crossover {
 #[allow(dead_code)]
 pub mod mutate {
     use crate::ea::{Individual, Mutate};
-    use super::super::TSP;
+    use crate::problem::Permutation;
+    use rand::Rng;
+    use super::super::{TSP, Distance, local};
 
-    pub struct Swap<'a> {
-        problem: &'a TSP,
+    pub struct Swap<'a, P: Permutation = TSP> {
+        problem: &'a P,
         probability: f64,
     }
 
-    impl Swap<'_> {
-        pub fn new<'a>(problem: &'a TSP, probability: f64) -> Swap<'a> {
+    impl<P: Permutation> Swap<'_, P> {
+        pub fn new<'a>(problem: &'a P, probability: f64) -> Swap<'a, P> {
             Swap {
                 problem: problem,
                 probability: probability,
@@ -289,18 +375,15 @@ pub mod mutate {
         }
     }
 
-    impl Mutate for Swap<'_> {
-        type Problem = TSP;
+    impl<P: Permutation> Mutate for Swap<'_, P> {
+        type Problem = P;
 
-        fn mutate(&self, individual: &mut Individual<TSP>) {
-            use rand::Rng;
+        fn mutate<R: Rng>(&self, individual: &mut Individual<P>, rng: &mut R) {
             use rand::distributions::{Distribution, Uniform};
-            use crate::problem::Problem;
-
             let distribution = Uniform::from(0..individual.genotype.len());
             for gene in 0..individual.genotype.len() {
-                if rand::thread_rng().gen_range(0.0, 1.0) < self.probability {
-                    let random_gene = distribution.sample(&mut rand::thread_rng());
+                if rng.gen_range(0.0, 1.0) < self.probability {
+                    let random_gene = distribution.sample(rng);
                     individual.genotype.swap(gene, random_gene);
                 }
             }
@@ -308,13 +391,13 @@ pub mod mutate {
         }
     }
 
-    pub struct Inversion<'a> {
-        problem: &'a TSP,
+    pub struct Inversion<'a, P: Permutation = TSP> {
+        problem: &'a P,
         probability: f64,
     }
 
-    impl Inversion<'_> {
-        pub fn new<'a>(problem: &'a TSP, probability: f64) -> Inversion<'a> {
+    impl<P: Permutation> Inversion<'_, P> {
+        pub fn new<'a>(problem: &'a P, probability: f64) -> Inversion<'a, P> {
             Inversion {
                 problem: problem,
                 probability: probability,
@@ -322,19 +405,16 @@ pub mod mutate {
         }
     }
 
-    impl Mutate for Inversion<'_> {
-        type Problem = TSP;
-
-        fn mutate(&self, individual: &mut Individual<TSP>) {
-            use rand::Rng;
-            use crate::problem::Problem;
+    impl<P: Permutation> Mutate for Inversion<'_, P> {
+        type Problem = P;
 
-            if rand::thread_rng().gen_range(0.0, 1.0) < self.probability {
+        fn mutate<R: Rng>(&self, individual: &mut Individual<P>, rng: &mut R) {
+            if rng.gen_range(0.0, 1.0) < self.probability {
                 use rand::distributions::{Distribution, Uniform};
 
                 let distribution = Uniform::from(0..individual.genotype.len());
-                let first = distribution.sample(&mut rand::thread_rng());
-                let second = distribution.sample(&mut rand::thread_rng());
+                let first = distribution.sample(rng);
+                let second = distribution.sample(rng);
 
                 if first != second {
                     let (lower, greater) = if first > second {
@@ -348,4 +428,150 @@ pub mod mutate {
             }
         }
     }
+
+    /// Applies another mutation, then improves the individual with
+    /// Lin–Kernighan and Or-opt with the given probability, turning the
+    /// algorithm memetic.
+    pub struct Improve<'a, M: Mutate<Problem = TSP<L>>, L = u64> {
+        problem: &'a TSP<L>,
+        mutate: M,
+        probability: f64,
+        local_search: Option<local::LocalSearch<'a, L>>,
+    }
+
+    impl<L: Distance, M: Mutate<Problem = TSP<L>>> Improve<'_, M, L> {
+        pub fn new<'a>(problem: &'a TSP<L>, mutate: M, probability: f64) -> Improve<'a, M, L> {
+            Improve {
+                problem: problem,
+                mutate: mutate,
+                probability: probability,
+                // the candidate lists are not worth building if they are never used
+                local_search: if probability > 0.0 {
+                    Some(local::LocalSearch::new(problem, local::CANDIDATES))
+                } else {
+                    None
+                },
+            }
+        }
+    }
+
+    impl<L: Distance, M: Mutate<Problem = TSP<L>>> Mutate for Improve<'_, M, L> {
+        type Problem = TSP<L>;
+
+        fn mutate<R: Rng>(&self, individual: &mut Individual<TSP<L>>, rng: &mut R) {
+            use crate::problem::Problem;
+
+            self.mutate.mutate(individual, rng);
+            if let Some(local_search) = &self.local_search {
+                if rng.gen_range(0.0, 1.0) < self.probability && local_search.improve(&mut individual.genotype) {
+                    individual.fitness = self.problem.fitness(&individual.genotype);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ea::{Individual, Initialize, Select, Crossover, Mutate};
+    use crate::problem::{Problem, validate_permutation};
+    use crate::tsp::{TSP, construct::Heuristic, parser::parse_problem_instance};
+    use super::{initialize, select, crossover, mutate};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::path::PathBuf;
+
+    /// Number of random cases every property is checked on.
+    const CASES: u64 = 50;
+
+    fn problem() -> TSP {
+        parse_problem_instance(&PathBuf::from("data/berlin52.tsp")).unwrap()
+    }
+
+    fn assert_valid(problem: &TSP, individual: &Individual<TSP>) {
+        if let Err(error) = validate_permutation(&individual.genotype, problem.dimension()) {
+            panic!("{}", error);
+        }
+        assert_eq!(individual.fitness, problem.fitness(&individual.genotype), "stale fitness");
+    }
+
+    fn population(problem: &TSP, seed: u64) -> Vec<Individual<TSP>> {
+        initialize::Random::new(problem).initialize(10, &mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    fn check_initialize<I: Initialize<Problem = TSP>>(problem: &TSP, operator: I) {
+        for seed in 0..5 {
+            let population = operator.initialize(20, &mut ChaCha8Rng::seed_from_u64(seed));
+            assert_eq!(population.len(), 20);
+            population.iter().for_each(|individual| assert_valid(problem, individual));
+        }
+    }
+
+    fn check_select<S: Select<Problem = TSP>>(problem: &TSP, operator: S) {
+        for seed in 0..CASES {
+            let population = population(problem, seed);
+            let selected = operator.select(&population, &mut ChaCha8Rng::seed_from_u64(seed));
+            assert!(population.iter().any(|individual| std::ptr::eq(individual, selected)));
+        }
+    }
+
+    fn check_crossover<C: Crossover<Problem = TSP>>(problem: &TSP, operator: C) {
+        for seed in 0..CASES {
+            let population = population(problem, seed);
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            assert_valid(problem, &operator.crossover(&population[0], &population[1], &mut rng));
+        }
+    }
+
+    fn check_mutate<M: Mutate<Problem = TSP>>(problem: &TSP, operator: M) {
+        for seed in 0..CASES {
+            let mut individual = population(problem, seed).remove(0);
+            operator.mutate(&mut individual, &mut ChaCha8Rng::seed_from_u64(seed));
+            assert_valid(problem, &individual);
+        }
+    }
+
+    #[test]
+    fn test_initialize() {
+        let problem = problem();
+        check_initialize(&problem, initialize::Random::new(&problem));
+        check_initialize(&problem, initialize::Greedy::new(&problem, 0.5));
+        check_initialize(&problem, initialize::Greedy::new(&problem, 1.0).distinct_starts(true));
+        check_initialize(&problem, initialize::LocalSearch::new(&problem, 0.2));
+        let heuristics = [
+            Heuristic::NearestInsertion, Heuristic::FarthestInsertion, Heuristic::CheapestInsertion,
+            Heuristic::Savings, Heuristic::Christofides,
+        ];
+        for heuristic in heuristics.iter() {
+            check_initialize(&problem, initialize::Construction::new(&problem, *heuristic, 0.2));
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let problem = problem();
+        check_select(&problem, select::Tournament::new(1));
+        check_select(&problem, select::Tournament::new(5));
+        check_select(&problem, select::RouletteWheel::new(0.0));
+        check_select(&problem, select::RouletteWheel::new(0.01));
+    }
+
+    #[test]
+    fn test_crossover() {
+        let problem = problem();
+        for probability in [0.0, 0.5, 1.0].iter() {
+            check_crossover(&problem, crossover::OX::new(&problem, *probability));
+            check_crossover(&problem, crossover::CX::new(&problem, *probability));
+        }
+    }
+
+    #[test]
+    fn test_mutate() {
+        let problem = problem();
+        for probability in [0.0, 0.1, 1.0].iter() {
+            check_mutate(&problem, mutate::Swap::new(&problem, *probability));
+            check_mutate(&problem, mutate::Inversion::new(&problem, *probability));
+            check_mutate(&problem, mutate::Improve::new(&problem, mutate::Inversion::new(&problem, 0.5), *probability));
+        }
+    }
 }
@@ -0,0 +1,33 @@
+/// Optimal tour lengths of the TSPLIB instances that come with the program,
+/// keyed by the file name without the extension.
+const KNOWN_OPTIMA: [(&str, u64); 9] = [
+    ("ali535", 202339),
+    ("berlin52", 7542),
+    ("fl417", 11861),
+    ("gr666", 294358),
+    ("kroA100", 21282),
+    ("kroA150", 26524),
+    ("kroA200", 29368),
+    ("nrw1379", 56638),
+    ("pr2392", 378032),
+];
+
+pub fn known_optimum(instance: &str) -> Option<u64> {
+    KNOWN_OPTIMA.iter()
+        .find(|(name, _)| *name == instance)
+        .map(|(_, optimum)| *optimum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_optimum() {
+        assert_eq!(known_optimum("berlin52"), Some(7542));
+        assert_eq!(known_optimum("pr2392"), Some(378032));
+        // the lookup is by the exact file stem
+        assert_eq!(known_optimum("berlin52.tsp"), None);
+        assert_eq!(known_optimum("berlin11_modified"), None);
+    }
+}
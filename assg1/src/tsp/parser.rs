@@ -1,26 +1,17 @@
 use quicli::prelude::*;
 
-use super::TSP;
+use super::{TSP, CoordinateSystem};
 use super::dm::DistanceMatrix;
 use std::path::PathBuf;
 
 pub fn parse_problem_instance(tsp_path: &PathBuf) -> Result<TSP, Error> {
-    enum CoordinateSystem {
-        Euclidean,
-        Geographical,
-    }
-
-    use std::fmt;
-
-    impl fmt::Debug for CoordinateSystem {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            write!(f, "{}", match &self {
-                Self::Euclidean => "Euclidean2D",
-                Self::Geographical => "Geographical",
-            })
-        }
-    }
+    parse_with_decimals(tsp_path, 0)
+}
 
+/// Parses the instance with the distances kept in units of `10^-decimals`
+/// instead of rounded to integers, so that the tour lengths are not skewed by
+/// the rounding of TSPLIB.
+pub fn parse_with_decimals(tsp_path: &PathBuf, decimals: u32) -> Result<TSP, Error> {
     let mut name: Option<String> = None;
     let mut dimension: Option<usize> = None;
     let mut coord_system: Option<CoordinateSystem> = None;
@@ -29,7 +20,7 @@ pub fn parse_problem_instance(tsp_path: &PathBuf) -> Result<TSP, Error> {
     
     let start = Instant::now();
     
-    let file = read_file(&tsp_path)?;
+    let file = read_file(tsp_path)?;
     let mut lines = file.lines();
     loop {
         match lines.next() {
@@ -58,58 +49,16 @@ pub fn parse_problem_instance(tsp_path: &PathBuf) -> Result<TSP, Error> {
     let coord_system: CoordinateSystem = coord_system
         .ok_or_else(|| format_err!("edge weight type missing"))?;
 
-    let nodes_iter = lines.take_while(|line| *line != "EOF");
-    let (dm, inferred_dimension) = match coord_system {
-        CoordinateSystem::Euclidean => {
-            let nodes: Vec<(f64, f64)> = nodes_iter.map(|coord_line| {
-                let values: Vec<&str> = coord_line.split_whitespace().collect();
-                values[1].parse::<f64>().and_then(|x| {
-                    values[2].parse::<f64>().map(|y| (x, y))
-                })
-            }).collect::<Result<Vec<(f64, f64)>, std::num::ParseFloatError>>()?;
-            info!("parsed the node coord section");
-            (
-                DistanceMatrix::new(&nodes, |a: &(f64, f64), b: &(f64, f64)| -> u32 {
-                    let xd = a.0 - b.0;
-                    let yd = a.1 - b.1;
-                    (xd * xd + yd * yd).sqrt().round() as u32
-                }),
-                nodes.len()
-            )
-        },
-        CoordinateSystem::Geographical => {
-            let nodes = nodes_iter.map(|coord_line| {
-                let values: Vec<&str> = coord_line.split_whitespace().collect();
-                values[1].parse::<f64>().and_then(|longitude| {
-                    values[2].parse::<f64>().map(|latitude| (latitude, longitude))
-                })
-            }).collect::<Result<Vec<(f64, f64)>, std::num::ParseFloatError>>()?;
-            info!("parsed the node coord section");
-            (
-                DistanceMatrix::new(&nodes, |a: &(f64, f64), b: &(f64, f64)| -> u32 {
-                    fn geo(x: &(f64, f64)) -> (f64, f64) {
-                        use std::f64::consts::PI;
-                        let deg = x.0.round();
-                        let min = x.0 - deg;
-                        let latitude = PI * (deg + 5.0 * min / 3.0) / 180.0;
-                        let deg = x.1.round();
-                        let min = x.1 - deg;
-                        let longitude = PI * (deg + 5.0 * min / 3.0) / 180.0;
-                        return (latitude, longitude);
-                    }
+    let coordinates: Vec<(f64, f64)> = lines.take_while(|line| *line != "EOF").map(|coord_line| {
+        let values: Vec<&str> = coord_line.split_whitespace().collect();
+        values[1].parse::<f64>().and_then(|x| {
+            values[2].parse::<f64>().map(|y| (x, y))
+        })
+    }).collect::<Result<Vec<(f64, f64)>, std::num::ParseFloatError>>()?;
+    info!("parsed the node coord section");
 
-                    let a = geo(a);
-                    let b = geo(b);
-                    let radius = 6378.388; // Earth's radius
-                    let q1 = (a.1 - b.1).cos();
-                    let q2 = (a.0 - b.0).cos();
-                    let q3 = (a.0 + b.0).cos();
-                    return (radius * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0) as u32;
-                }),
-                nodes.len()
-            )
-        }
-    };
+    let dm = distance_matrix(&coord_system, &coordinates, decimals)?;
+    let inferred_dimension = coordinates.len();
 
     let dimension: usize = dimension.unwrap_or_else(|| {
         warn!("dimension not provided explicitly, inferred {}", inferred_dimension);
@@ -123,5 +72,100 @@ pub fn parse_problem_instance(tsp_path: &PathBuf) -> Result<TSP, Error> {
         name: name,
         dimension: dimension,
         dm: dm,
+        decimals: decimals,
+        coord_system: coord_system,
+        coordinates: coordinates,
+        length: std::marker::PhantomData,
     })
 }
+
+/// The distance between two nodes before any rounding, with the geographical
+/// coordinates converted and measured the way TSPLIB defines it.
+pub(crate) fn exact_distance(coord_system: &CoordinateSystem, a: &(f64, f64), b: &(f64, f64)) -> f64 {
+    match coord_system {
+        CoordinateSystem::Euclidean => {
+            let xd = a.0 - b.0;
+            let yd = a.1 - b.1;
+            (xd * xd + yd * yd).sqrt()
+        },
+        CoordinateSystem::Geographical => {
+            fn geo(x: &(f64, f64)) -> (f64, f64) {
+                use std::f64::consts::PI;
+                let deg = x.1.round();
+                let min = x.1 - deg;
+                let latitude = PI * (deg + 5.0 * min / 3.0) / 180.0;
+                let deg = x.0.round();
+                let min = x.0 - deg;
+                let longitude = PI * (deg + 5.0 * min / 3.0) / 180.0;
+                return (latitude, longitude);
+            }
+
+            let a = geo(a);
+            let b = geo(b);
+            let radius = 6378.388; // Earth's radius
+            let q1 = (a.1 - b.1).cos();
+            let q2 = (a.0 - b.0).cos();
+            let q3 = (a.0 + b.0).cos();
+            return radius * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos();
+        }
+    }
+}
+
+/// Turns an exact distance into an integer. With no decimals, this is the
+/// `nint` rounding of TSPLIB (or its `(int)(d + 1.0)` for geographical
+/// distances); otherwise the distance is kept in units of `10^-decimals`,
+/// rounded to the nearest one.
+pub(crate) fn round_distance(coord_system: &CoordinateSystem, exact: f64, decimals: u32) -> f64 {
+    match (coord_system, decimals) {
+        (CoordinateSystem::Euclidean, 0) => exact.round(),
+        (CoordinateSystem::Geographical, 0) => (exact + 1.0).trunc(),
+        (_, decimals) => (exact * 10f64.powi(decimals as i32)).round(),
+    }
+}
+
+/// Computes the distances between the nodes the way TSPLIB defines them for
+/// the edge weight type, keeping the given number of decimals. Fails when a
+/// distance does not fit in 32 bits.
+pub(crate) fn distance_matrix(coord_system: &CoordinateSystem, coordinates: &Vec<(f64, f64)>, decimals: u32)
+    -> Result<DistanceMatrix, Error>
+{
+    DistanceMatrix::new(coordinates, |a: &(f64, f64), b: &(f64, f64)| -> Result<u32, Error> {
+        let distance = round_distance(coord_system, exact_distance(coord_system, a, b), decimals);
+        ensure!(distance <= u32::MAX as f64,
+            "the distance {} between {:?} and {:?} overflows 32 bits", distance, a, b);
+        Ok(distance as u32)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::Problem;
+
+    #[test]
+    fn test_decimals() {
+        for instance in ["data/berlin52.tsp", "data/gr666.tsp"].iter() {
+            let path = PathBuf::from(instance);
+            let rounded = parse_problem_instance(&path).unwrap();
+            let precise = parse_with_decimals(&path, 3).unwrap();
+            let tour: Vec<usize> = (0..rounded.dimension()).collect();
+            assert_eq!(rounded.fitness(&tour), rounded.rounded_length(&tour));
+            assert_eq!(precise.rounded_length(&tour), rounded.fitness(&tour));
+            // the rounding moves every distance by at most a unit
+            let difference = precise.fitness(&tour) as f64 / 1000.0 - rounded.fitness(&tour) as f64;
+            assert!(difference.abs() <= tour.len() as f64);
+            let exact = rounded.measured::<f64>().fitness(&tour);
+            assert!((exact - precise.fitness(&tour) as f64 / 1000.0).abs() <= tour.len() as f64 / 1000.0);
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let path = std::env::temp_dir().join("assg1-overflow.tsp");
+        write_to_file(&path, "NAME: far\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n\
+            1 0 0\n2 1e10 0\nEOF\n").unwrap();
+        let problem = parse_problem_instance(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(problem.is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use super::{TSP, CoordinateSystem, Distance};
+use crate::problem::Length;
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 500.0;
+const MARGIN: f64 = 60.0;
+
+fn header(width: f64, height: f64) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" \
+        font-family=\"sans-serif\" font-size=\"12\">\n<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        width, height
+    )
+}
+
+/// Escapes the characters with a special meaning in XML text and attributes.
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|character| match character {
+            '&' => "&amp;".to_owned(),
+            '<' => "&lt;".to_owned(),
+            '>' => "&gt;".to_owned(),
+            '"' => "&quot;".to_owned(),
+            '\'' => "&apos;".to_owned(),
+            character => character.to_string(),
+        })
+        .collect()
+}
+
+/// Draws the tour over the node coordinates, with the y axis pointing up. The
+/// nodes of geographical instances are drawn with the longitude on the x axis.
+pub fn tour<L: Distance>(problem: &TSP<L>, tour: &[usize]) -> String {
+    use crate::problem::Problem;
+
+    let points: Vec<(f64, f64)> = problem.coordinates().iter()
+        .map(|(x, y)| match problem.coord_system() {
+            CoordinateSystem::Euclidean => (*x, *y),
+            CoordinateSystem::Geographical => (*y, *x),
+        })
+        .collect();
+    let (min_x, max_x, min_y, max_y) = points.iter().fold(
+        (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY),
+        |(min_x, max_x, min_y, max_y), (x, y)| (min_x.min(*x), max_x.max(*x), min_y.min(*y), max_y.max(*y))
+    );
+    let side = WIDTH - 2.0 * MARGIN;
+    let scale = side / (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+    let project = |(x, y): (f64, f64)| (MARGIN + (x - min_x) * scale, MARGIN + (max_y - y) * scale);
+    let height = 2.0 * MARGIN + (max_y - min_y) * scale;
+
+    let mut svg = header(WIDTH, height);
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\">{} — {} nodes, length {}</text>\n",
+        MARGIN, MARGIN / 2.0, escape(problem.name.as_deref().unwrap_or("tour")), tour.len(),
+        problem.fitness(&tour.to_vec())
+    ));
+    let path = tour.iter()
+        .map(|node| project(points[*node]))
+        .enumerate()
+        .map(|(i, (x, y))| format!("{}{:.2} {:.2}", if i == 0 { "M" } else { "L" }, x, y))
+        .collect::<Vec<String>>()
+        .join(" ");
+    svg.push_str(&format!("<path d=\"{} Z\" fill=\"none\" stroke=\"steelblue\" stroke-width=\"1.5\"/>\n", path));
+    for point in &points {
+        let (x, y) = project(*point);
+        svg.push_str(&format!("<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"2\" fill=\"black\"/>\n", x, y));
+    }
+    svg.push_str("</svg>\n");
+    return svg;
+}
+
+/// Draws the best, average and worst fitness in every generation, as returned
+/// by `Cohorter::curves`.
+pub fn convergence<L: Length>(bests: &[L], avgs: &[f64], worsts: &[L]) -> String {
+    let generations = bests.len();
+    let min = bests.iter().map(|value| value.to_f64()).fold(f64::INFINITY, f64::min);
+    let min = if min.is_finite() { min } else { 0.0 };
+    let max = worsts.iter().map(|value| value.to_f64()).fold(0.0, f64::max);
+    let plot_width = WIDTH - 2.0 * MARGIN;
+    let plot_height = HEIGHT - 2.0 * MARGIN;
+    let project = |generation: usize, value: f64| (
+        MARGIN + generation as f64 / (generations.max(2) - 1) as f64 * plot_width,
+        HEIGHT - MARGIN - (value - min) / (max - min).max(f64::EPSILON) * plot_height,
+    );
+
+    let mut svg = header(WIDTH, HEIGHT);
+    svg.push_str(&format!(
+        "<path d=\"M{0} {1} V{2} H{3}\" fill=\"none\" stroke=\"black\"/>\n",
+        MARGIN, MARGIN, HEIGHT - MARGIN, WIDTH - MARGIN
+    ));
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n\
+        <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n",
+        MARGIN - 5.0, MARGIN + 4.0, max, MARGIN - 5.0, HEIGHT - MARGIN + 4.0, min
+    ));
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">0</text>\n\
+        <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>\n\
+        <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">generation</text>\n",
+        MARGIN, HEIGHT - MARGIN + 18.0, WIDTH - MARGIN, HEIGHT - MARGIN + 18.0, generations.saturating_sub(1),
+        WIDTH / 2.0, HEIGHT - MARGIN + 36.0
+    ));
+
+    let curves: [(&str, &str, Vec<f64>); 3] = [
+        ("best", "seagreen", bests.iter().map(|value| value.to_f64()).collect()),
+        ("avg", "steelblue", avgs.to_vec()),
+        ("worst", "firebrick", worsts.iter().map(|value| value.to_f64()).collect()),
+    ];
+    for (i, (label, color, values)) in curves.iter().enumerate() {
+        let points = values.iter()
+            .enumerate()
+            .map(|(generation, value)| {
+                let (x, y) = project(generation, *value);
+                format!("{:.2},{:.2}", x, y)
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+        svg.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\"/>\n", points, color
+        ));
+        let legend_y = MARGIN + 16.0 * i as f64;
+        svg.push_str(&format!(
+            "<line x1=\"{0}\" y1=\"{1}\" x2=\"{2}\" y2=\"{1}\" stroke=\"{3}\" stroke-width=\"2\"/>\n\
+            <text x=\"{4}\" y=\"{5}\">{6}</text>\n",
+            WIDTH - MARGIN - 80.0, legend_y, WIDTH - MARGIN - 60.0, color, WIDTH - MARGIN - 55.0, legend_y + 4.0, label
+        ));
+    }
+    svg.push_str("</svg>\n");
+    return svg;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tsp::parser::parse_problem_instance;
+
+    #[test]
+    fn test_tour() {
+        let mut problem = parse_problem_instance(&"data/berlin11_modified.tsp".into()).unwrap();
+        problem.name = Some("berlin <11> & \"modified\"".to_owned());
+        let nodes: Vec<usize> = (0..problem.dimension).collect();
+        let svg = tour(&problem, &nodes);
+
+        assert!(svg.contains("berlin &lt;11&gt; &amp; &quot;modified&quot;"));
+        assert_eq!(svg.matches("<circle").count(), problem.dimension);
+        let path = svg.split("<path d=\"").nth(1).unwrap();
+        let path = &path[..path.find('"').unwrap()];
+        assert_eq!(path.matches(&['M', 'L'][..]).count(), problem.dimension);
+        assert!(path.ends_with(" Z"));
+    }
+}
@@ -0,0 +1,296 @@
+use quicli::prelude::*;
+use rand::Rng;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::config::Configuration;
+use crate::stats;
+use assg1::tsp::TSP;
+
+/// The values a parameter is sampled from, uniformly.
+#[derive(Clone, Debug, PartialEq)]
+enum Range {
+    Integer(i64, i64),
+    Real(f64, f64),
+}
+
+/// One of the values of a key, e.g. `tournament:2..50` or `random`.
+#[derive(Clone, Debug, PartialEq)]
+struct Alternative {
+    operator: String,
+    range: Option<Range>,
+}
+
+impl Alternative {
+    fn with_value(&self, value: String) -> String {
+        if self.operator.is_empty() {
+            value
+        } else {
+            format!("{}:{}", self.operator, value)
+        }
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R) -> String {
+        match self.range {
+            None => self.operator.clone(),
+            Some(Range::Integer(low, high)) => self.with_value(rng.gen_range(low, high + 1).to_string()),
+            Some(Range::Real(low, high)) => self.with_value(rng.gen_range(low, high).to_string()),
+        }
+    }
+
+    fn lowest(&self) -> String {
+        match self.range {
+            None => self.operator.clone(),
+            Some(Range::Integer(low, _)) => self.with_value(low.to_string()),
+            Some(Range::Real(low, _)) => self.with_value(low.to_string()),
+        }
+    }
+}
+
+/// The parameter ranges configurations are sampled from.
+///
+/// A space is written like a configuration, except that a value can hold
+/// alternatives separated by `|` and parameters can be ranges `LOW..HIGH`,
+/// e.g. `select=tournament:2..50|roulette:0.001..0.1,crossover=ox:0.5..1.0,pop=100..500`.
+/// The bounds of integer ranges are inclusive, and so is the lower bound of
+/// real ranges.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Space {
+    keys: Vec<(String, Vec<Alternative>)>,
+}
+
+impl FromStr for Space {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Space, Error> {
+        fn range(source: &str) -> Result<Range, Error> {
+            let dots_index = source.find("..").unwrap();
+            let (low, high) = (&source[..dots_index], &source[dots_index + 2..]);
+            let range = match (low.parse::<i64>(), high.parse::<i64>()) {
+                (Ok(low), Ok(high)) => Range::Integer(low, high),
+                _ => Range::Real(low.parse::<f64>()?, high.parse::<f64>()?),
+            };
+            match range {
+                Range::Integer(low, high) if low > high => Err(format_err!("empty range {:?}", source)),
+                Range::Real(low, high) if low >= high => Err(format_err!("empty range {:?}", source)),
+                _ => Ok(range),
+            }
+        }
+
+        let mut keys = Vec::new();
+        for pair in source.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let equals_index = pair.find('=')
+                .ok_or_else(|| format_err!("expected a key=value pair, got {:?}", pair))?;
+            let (key, value) = (&pair[..equals_index], &pair[equals_index + 1..]);
+            let alternatives = value.split('|')
+                .map(|alternative| match (alternative.rfind(':'), alternative.contains("..")) {
+                    (Some(colon_index), true) => Ok(Alternative {
+                        operator: alternative[..colon_index].to_owned(),
+                        range: Some(range(&alternative[colon_index + 1..])?),
+                    }),
+                    (None, true) => Ok(Alternative {
+                        operator: String::new(),
+                        range: Some(range(alternative)?),
+                    }),
+                    (_, false) => Ok(Alternative {
+                        operator: alternative.to_owned(),
+                        range: None,
+                    }),
+                })
+                .collect::<Result<Vec<Alternative>, Error>>()?;
+            for alternative in &alternatives {
+                // the lowest value of every alternative has to make a valid configuration
+                format!("{}={}", key, alternative.lowest()).parse::<Configuration>()?;
+            }
+            keys.push((key.to_owned(), alternatives));
+        }
+        Ok(Space {
+            keys: keys,
+        })
+    }
+}
+
+impl Space {
+    /// Samples a configuration, taking the keys missing from the space from
+    /// `base`.
+    pub fn sample<R: Rng>(&self, base: &Configuration, rng: &mut R) -> Result<Configuration, Error> {
+        use rand::seq::SliceRandom;
+
+        let mut source = base.to_string();
+        for (key, alternatives) in &self.keys {
+            source.push_str(&format!(",{}={}", key, alternatives.choose(rng).unwrap().sample(rng)));
+        }
+        source.parse::<Configuration>()
+    }
+}
+
+/// The best fitness of a single run of a configuration during the race.
+pub struct Evaluation {
+    pub step: usize,
+    pub instance: String,
+    pub configuration: Configuration,
+    pub best: u64,
+}
+
+/// A configuration that survived the race.
+pub struct Survivor {
+    pub configuration: Configuration,
+    pub steps: usize,
+    pub average_rank: f64,
+}
+
+pub struct Race {
+    pub evaluations: Vec<Evaluation>,
+    /// Ordered from the best average rank.
+    pub survivors: Vec<Survivor>,
+}
+
+/// Races the candidates F-race style: in every step the surviving candidates
+/// run once on the next training instance with a shared seed, and starting
+/// from step `first_test`, whenever the Friedman test rejects equality at the
+/// level `alpha`, the candidates ranked worse than the best one by more than
+/// the Bonferroni-Dunn critical difference are discarded. The race ends when
+/// a single candidate remains or the next step would exceed the budget of
+/// runs.
+pub fn race(candidates: Vec<Configuration>, problems: &[(String, TSP)], budget: usize, first_test: usize,
+    alpha: f64, seed: u64) -> Result<Race, Error>
+{
+    ensure!(!candidates.is_empty(), "no candidates to race");
+    ensure!(!problems.is_empty(), "no training instances");
+    ensure!(budget >= candidates.len(), "a budget of {} runs cannot run {} candidates even once",
+        budget, candidates.len());
+
+    let mut alive: Vec<usize> = (0..candidates.len()).collect();
+    let mut results: Vec<Vec<f64>> = vec![Vec::new(); candidates.len()];
+    let mut evaluations = Vec::new();
+    let mut step = 0;
+    while alive.len() > 1 && evaluations.len() + alive.len() <= budget {
+        let (instance, problem) = &problems[step % problems.len()];
+        let run_seed = seed.wrapping_add(step as u64);
+        for candidate in &alive {
+            let best = candidates[*candidate].bests(problem, 1, run_seed)?[0];
+            results[*candidate].push(best as f64);
+            evaluations.push(Evaluation {
+                step: step,
+                instance: instance.clone(),
+                configuration: candidates[*candidate].clone(),
+                best: best,
+            });
+        }
+        step += 1;
+        info!("finished step #{} with {} candidates", step, alive.len());
+
+        if step >= first_test {
+            let test = stats::friedman(&blocks(&results, &alive, step));
+            if test.p < alpha {
+                let k = alive.len() as f64;
+                let critical_difference = stats::normal_quantile(1.0 - alpha / (2.0 * (k - 1.0)))
+                    * (k * (k + 1.0) / (6.0 * step as f64)).sqrt();
+                let best_rank = test.average_ranks.iter().cloned().fold(f64::INFINITY, f64::min);
+                alive = alive.iter().zip(test.average_ranks.iter())
+                    .filter(|(_, rank)| **rank - best_rank <= critical_difference)
+                    .map(|(candidate, _)| *candidate)
+                    .collect();
+                info!("{} candidates survived step #{}", alive.len(), step);
+            }
+        }
+    }
+
+    let average_ranks = if step > 0 && alive.len() > 1 {
+        stats::friedman(&blocks(&results, &alive, step)).average_ranks
+    } else {
+        vec![1.0; alive.len()]
+    };
+    let mut survivors: Vec<Survivor> = alive.iter().zip(average_ranks)
+        .map(|(candidate, rank)| Survivor {
+            configuration: candidates[*candidate].clone(),
+            steps: results[*candidate].len(),
+            average_rank: rank,
+        })
+        .collect();
+    survivors.sort_by(|a, b| a.average_rank.partial_cmp(&b.average_rank).unwrap());
+    Ok(Race {
+        evaluations: evaluations,
+        survivors: survivors,
+    })
+}
+
+/// The results of the alive candidates in every step, one block per step.
+fn blocks(results: &[Vec<f64>], alive: &[usize], steps: usize) -> Vec<Vec<f64>> {
+    (0..steps)
+        .map(|step| alive.iter().map(|candidate| results[*candidate][step]).collect())
+        .collect()
+}
+
+pub fn print_survivors(race: &Race) {
+    println!("% configuration & runs & average rank");
+    for survivor in &race.survivors {
+        println!("{} & {} & {:.2} \\\\", survivor.configuration, survivor.steps, survivor.average_rank);
+    }
+}
+
+pub fn dump_evaluations(race: &Race, path: &PathBuf) -> Result<(), Error> {
+    let output = race.evaluations.iter()
+        .map(|evaluation| format!("{};{};{};{}",
+            evaluation.step, evaluation.instance, evaluation.configuration, evaluation.best))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    write_to_file(path, &output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Crossover, Selection};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_sample_space() {
+        let space = "select=tournament:2..4,crossover=ox:0.5..1.0|cx:0.5..1.0,pop=10..20".parse::<Space>().unwrap();
+        let base = "gen=5".parse::<Configuration>().unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for _ in 0..50 {
+            let configuration = space.sample(&base, &mut rng).unwrap();
+            assert_eq!(configuration.generations, 5);
+            assert!((10..=20).contains(&configuration.pop_size));
+            match configuration.selection {
+                Selection::Tournament(tour_size) => assert!((2..=4).contains(&tour_size)),
+                selection => panic!("unexpected selection {:?}", selection),
+            }
+            match configuration.crossover {
+                Crossover::OX(probability) | Crossover::CX(probability) =>
+                    assert!((0.5..1.0).contains(&probability)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_race() {
+        use assg1::tsp::parser::parse_problem_instance;
+
+        let problems: Vec<(String, TSP)> = ["berlin52", "kroA100"].iter()
+            .map(|name| (name.to_string(), parse_problem_instance(&PathBuf::from(format!("data/{}.tsp", name))).unwrap()))
+            .collect();
+        let good = "init=greedy:1.0,pop=10,gen=5".parse::<Configuration>().unwrap();
+        // random tours improved for a single generation lose every step
+        let dominated = "init=random,pop=2,gen=1".parse::<Configuration>().unwrap();
+        let race = race(vec![dominated, good.clone()], &problems, 20, 2, 0.05, 0).unwrap();
+
+        assert_eq!(race.survivors.len(), 1);
+        assert_eq!(race.survivors[0].configuration.to_string(), good.to_string());
+        // the Friedman test rejects equality of two candidates after four
+        // steps with the same ranks, each step running both of them
+        assert_eq!(race.survivors[0].steps, 4);
+        assert_eq!(race.evaluations.len(), 8);
+    }
+
+    #[test]
+    fn test_parse_space_errors() {
+        assert!("select=tournament:5..2".parse::<Space>().is_err());
+        assert!("select=tournament:0.5..0.9".parse::<Space>().is_err());
+        assert!("crossover=pmx:0.1..0.5".parse::<Space>().is_err());
+        assert!("select".parse::<Space>().is_err());
+    }
+}
@@ -0,0 +1,146 @@
+#![allow(clippy::redundant_field_names)]
+
+use assg1::ea::{Crossover, Evolutionary, Individual, Initialize, Mutate, Select};
+use assg1::log::Log;
+use assg1::problem::Problem;
+use assg1::tsp::{TSP, logs::Discoverer, ops, parser::parse_problem_instance};
+use rand::Rng;
+use std::path::PathBuf;
+
+#[test]
+fn test_tsp() {
+    let problem: TSP = parse_problem_instance(&PathBuf::from("data/berlin52.tsp")).unwrap();
+    let mut discoverer = Discoverer::new();
+    Evolutionary::new(
+        ops::initialize::Random::new(&problem),
+        ops::select::Tournament::new(5),
+        ops::crossover::OX::new(&problem, 0.8),
+        ops::mutate::Inversion::new(&problem, 0.1),
+        100,
+        50,
+    ).validate(&problem).seed(0).run(&mut vec![&mut discoverer]).unwrap();
+    discoverer.carry();
+
+    assert!(problem.validate(&discoverer.best_solution).is_ok());
+    assert_eq!(problem.fitness(&discoverer.best_solution), discoverer.bests()[0]);
+    // random tours of berlin52 are about 30000 long
+    assert!(discoverer.bests()[0] < 20000);
+}
+
+#[test]
+fn test_exact_tsp() {
+    let problem: TSP<f64> = parse_problem_instance(&PathBuf::from("data/berlin52.tsp")).unwrap().measured();
+    let mut discoverer = Discoverer::new();
+    Evolutionary::new(
+        ops::initialize::Greedy::new(&problem, 0.1),
+        ops::select::Tournament::new(5),
+        ops::crossover::OX::new(&problem, 0.8),
+        ops::mutate::Improve::new(&problem, ops::mutate::Inversion::new(&problem, 0.1), 0.1),
+        100,
+        50,
+    ).validate(&problem).seed(0).run(&mut vec![&mut discoverer]).unwrap();
+    discoverer.carry();
+
+    assert!(problem.validate(&discoverer.best_solution).is_ok());
+    assert_eq!(problem.fitness(&discoverer.best_solution), discoverer.bests()[0]);
+    // the exact optimum of berlin52 is about 7544.37, the rounded one 7542
+    assert!(discoverer.bests()[0] > 7544.0);
+    assert!(discoverer.bests()[0] < 9000.0);
+}
+
+/// Minimizes the number of unset bits.
+struct OneMax {
+    size: usize,
+}
+
+impl Problem for OneMax {
+    type Solution = Vec<bool>;
+    type Measure = usize;
+
+    fn fitness(&self, solution: &Vec<bool>) -> usize {
+        solution.iter().filter(|bit| !**bit).count()
+    }
+}
+
+struct RandomBits<'a>(&'a OneMax);
+
+impl<'a> Initialize for RandomBits<'a> {
+    type Problem = OneMax;
+
+    fn initialize<R: Rng>(&self, pop_size: usize, rng: &mut R) -> Vec<Individual<OneMax>> {
+        (0..pop_size)
+            .map(|_| {
+                let genotype: Vec<bool> = (0..self.0.size).map(|_| rng.gen()).collect();
+                Individual {
+                    fitness: self.0.fitness(&genotype),
+                    genotype: genotype,
+                }
+            })
+            .collect()
+    }
+}
+
+struct BinaryTournament;
+
+impl Select for BinaryTournament {
+    type Problem = OneMax;
+
+    fn select<'a, R: Rng>(&self, population: &'a Vec<Individual<OneMax>>, rng: &mut R) -> &'a Individual<OneMax> {
+        let a = &population[rng.gen_range(0, population.len())];
+        let b = &population[rng.gen_range(0, population.len())];
+        if a.fitness <= b.fitness { a } else { b }
+    }
+}
+
+struct Uniform<'a>(&'a OneMax);
+
+impl<'a> Crossover for Uniform<'a> {
+    type Problem = OneMax;
+
+    fn crossover<'b, R: Rng>(&self, a: &'b Individual<OneMax>, b: &'b Individual<OneMax>, rng: &mut R)
+        -> Individual<OneMax>
+    {
+        let genotype: Vec<bool> = a.genotype.iter().zip(b.genotype.iter())
+            .map(|(a, b)| if rng.gen() { *a } else { *b })
+            .collect();
+        Individual {
+            fitness: self.0.fitness(&genotype),
+            genotype: genotype,
+        }
+    }
+}
+
+struct BitFlip<'a>(&'a OneMax);
+
+impl<'a> Mutate for BitFlip<'a> {
+    type Problem = OneMax;
+
+    fn mutate<R: Rng>(&self, individual: &mut Individual<OneMax>, rng: &mut R) {
+        let bit = rng.gen_range(0, self.0.size);
+        individual.genotype[bit] = !individual.genotype[bit];
+        individual.fitness = self.0.fitness(&individual.genotype);
+    }
+}
+
+#[derive(Default)]
+struct Best(Option<usize>);
+
+impl Log<(Vec<bool>, usize)> for Best {
+    fn log(&mut self, value: &(Vec<bool>, usize)) {
+        let (_, measure) = value;
+        self.0 = Some(self.0.map_or(*measure, |best| best.min(*measure)));
+    }
+}
+
+#[test]
+fn test_custom_problem() {
+    let problem = OneMax { size: 32 };
+    let mut best = Best::default();
+    Evolutionary::new(RandomBits(&problem), BinaryTournament, Uniform(&problem), BitFlip(&problem), 50, 100)
+        .validate(&problem)
+        .seed(0)
+        .run(&mut vec![&mut best])
+        .unwrap();
+
+    assert_eq!(best.0, Some(0));
+}
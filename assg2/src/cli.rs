@@ -1,9 +1,10 @@
 use snafu::{Snafu};
 use structopt::StructOpt;
 use std::path::PathBuf;
-use super::csp::solvers::{VariableSelector, ValueSelector};
+use super::csp::solvers::{Limit, VariableSelector, ValueSelector};
 
 #[derive(Debug, Snafu)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     #[snafu(display("{:?}", name))]
     InvalidAlgorithmName { name: String },
@@ -11,6 +12,10 @@ pub enum Error {
     InvalidVariableSelectorHeuristicName { name: String },
     #[snafu(display("{:?}", name))]
     InvalidValueSelectorHeuristicName { name: String },
+    #[snafu(display("{:?}", name))]
+    InvalidSolutionLimit { name: String },
+    #[snafu(display("{:?}", name))]
+    InvalidReportFormatName { name: String },
 }
 
 use std::fmt;
@@ -19,7 +24,10 @@ impl fmt::Debug for Algorithm {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Algorithm::Backtracking => write!(f, "bt"),
-            Algorithm::ForwardChecking => write!(f, "fc")
+            Algorithm::ForwardChecking => write!(f, "fc"),
+            Algorithm::MaintainingArcConsistency => write!(f, "mac"),
+            Algorithm::ConflictDirectedBackjumping => write!(f, "cbj"),
+            Algorithm::ConflictDirectedBackjumpingWithNogoods => write!(f, "cbj-ng")
         }
     }
 }
@@ -29,7 +37,20 @@ impl fmt::Debug for VariableSelector {
         match self {
             VariableSelector::OrderOfDefinition => write!(f, "def"),
             VariableSelector::MostConstrainedVariable => write!(f, "mcv"),
-            VariableSelector::Random => write!(f, "rand")
+            VariableSelector::Random => write!(f, "rand"),
+            VariableSelector::Degree => write!(f, "deg"),
+            VariableSelector::MostConstrainedVariableDegree => write!(f, "mcv+deg"),
+            VariableSelector::DomainOverDegree => write!(f, "dom/deg"),
+            VariableSelector::DomainOverWeightedDegree => write!(f, "dom/wdeg")
+        }
+    }
+}
+
+impl fmt::Debug for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::All => write!(f, "all"),
+            Limit::First(count) => write!(f, "{}", count)
         }
     }
 }
@@ -50,14 +71,20 @@ pub struct Config {
     #[structopt(flatten)]
     pub verbosity: clap_verbosity_flag::Verbosity,
     /// The algorithm used to find solutions to the problem
-    #[structopt(short = "a", name = "ALGORITHM", parse(try_from_str = parse_algorithm), possible_values = &["bt", "fc"])]
+    #[structopt(short = "a", name = "ALGORITHM", parse(try_from_str = parse_algorithm), possible_values = &["bt", "fc", "mac", "cbj", "cbj-ng"])]
     pub algorithm: Algorithm,
     /// The variable selection heuristic
-    #[structopt(short = "r", name = "VARIABLE SELECTION HEURISTIC", parse(try_from_str = variable_selection_heuristic), possible_values = &["def", "mcv", "rand"])]
+    #[structopt(short = "r", name = "VARIABLE SELECTION HEURISTIC", parse(try_from_str = variable_selection_heuristic), possible_values = &["def", "mcv", "rand", "deg", "mcv+deg", "dom/deg", "dom/wdeg"])]
     pub variable_selection_heuristic: VariableSelector,
     /// The value selection heuristic
     #[structopt(short = "l", name = "VALUE SELECTION HEURISTIC", parse(try_from_str = value_selection_heuristic), possible_values = &["def", "lcv", "lov", "rand"])]
     pub value_selection_heuristic: ValueSelector,
+    /// The number of solutions after which the search stops, or "all"
+    #[structopt(short = "n", name = "SOLUTION LIMIT", default_value = "all", parse(try_from_str = solution_limit))]
+    pub limit: Limit,
+    /// The format of the search report printed after the solutions
+    #[structopt(short = "s", name = "REPORT FORMAT", parse(try_from_str = report_format), possible_values = &["csv", "json"])]
+    pub report_format: Option<ReportFormat>,
     #[structopt(subcommand)]
     pub problem: Problem,
 }
@@ -65,12 +92,18 @@ pub struct Config {
 pub enum Algorithm {
     Backtracking,
     ForwardChecking,
+    MaintainingArcConsistency,
+    ConflictDirectedBackjumping,
+    ConflictDirectedBackjumpingWithNogoods,
 }
 
 fn parse_algorithm(source: &str) -> std::result::Result<Algorithm, Error> {
     match source {
         "bt" => Ok(Algorithm::Backtracking),
         "fc" => Ok(Algorithm::ForwardChecking),
+        "mac" => Ok(Algorithm::MaintainingArcConsistency),
+        "cbj" => Ok(Algorithm::ConflictDirectedBackjumping),
+        "cbj-ng" => Ok(Algorithm::ConflictDirectedBackjumpingWithNogoods),
         _ => Err(Error::InvalidAlgorithmName { name: source.to_owned() })
     }
 }
@@ -80,6 +113,10 @@ fn variable_selection_heuristic(source: &str) -> std::result::Result<VariableSel
         "def" => Ok(VariableSelector::OrderOfDefinition),
         "mcv" => Ok(VariableSelector::MostConstrainedVariable),
         "rand" => Ok(VariableSelector::Random),
+        "deg" => Ok(VariableSelector::Degree),
+        "mcv+deg" => Ok(VariableSelector::MostConstrainedVariableDegree),
+        "dom/deg" => Ok(VariableSelector::DomainOverDegree),
+        "dom/wdeg" => Ok(VariableSelector::DomainOverWeightedDegree),
         _ => Err(Error::InvalidVariableSelectorHeuristicName { name: source.to_owned() })
     }
 }
@@ -94,6 +131,30 @@ fn value_selection_heuristic(source: &str) -> std::result::Result<ValueSelector,
     }
 }
 
+fn solution_limit(source: &str) -> std::result::Result<Limit, Error> {
+    match source {
+        "all" => Ok(Limit::All),
+        _ => match source.parse::<usize>() {
+            Ok(count) if count > 0 => Ok(Limit::First(count)),
+            _ => Err(Error::InvalidSolutionLimit { name: source.to_owned() })
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ReportFormat {
+    CSV,
+    JSON,
+}
+
+fn report_format(source: &str) -> std::result::Result<ReportFormat, Error> {
+    match source {
+        "csv" => Ok(ReportFormat::CSV),
+        "json" => Ok(ReportFormat::JSON),
+        _ => Err(Error::InvalidReportFormatName { name: source.to_owned() })
+    }
+}
+
 #[derive(Debug, StructOpt)]
 pub enum Problem {
     #[structopt(name = "jolka")]
@@ -112,7 +173,32 @@ pub enum Problem {
         sudoku_path: PathBuf,
         /// Id of the sudoku board
         #[structopt(name = "SUDOKU ID")]
-        sudoku_id: String
+        sudoku_id: String,
+        /// Constrain every row, column and box with a single all-different constraint
+        #[structopt(short = "g", long = "all-different")]
+        all_different: bool,
+        /// Require different numbers on both diagonals
+        #[structopt(long = "diagonal")]
+        diagonal: bool,
+        /// Require different numbers in the four extra boxes of a hyper-sudoku
+        #[structopt(long = "hyper")]
+        hyper: bool,
+        /// File with the regions of a jigsaw sudoku, replacing the boxes
+        #[structopt(long = "jigsaw", name = "REGIONS FILE", parse(from_os_str))]
+        regions_path: Option<PathBuf>,
+        /// File with the cages of a killer sudoku
+        #[structopt(long = "killer", name = "CAGES FILE", parse(from_os_str))]
+        cages_path: Option<PathBuf>,
+    },
+    /// Solves every sudoku board in the file and prints the search reports
+    #[structopt(name = "bench")]
+    Bench {
+        /// CSV file containing sudoku boards
+        #[structopt(name = "SUDOKU FILE", parse(from_os_str))]
+        sudoku_path: PathBuf,
+        /// Constrain every row, column and box with a single all-different constraint
+        #[structopt(short = "g", long = "all-different")]
+        all_different: bool,
     }
 }
 
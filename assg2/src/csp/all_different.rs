@@ -0,0 +1,313 @@
+use super::{Constraint, CSP, Domains};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+
+/// Requires the variables to take different values. Propagates with the
+/// matching-based filtering of Régin, which removes every value that cannot
+/// be a part of any assignment of different values to all the variables, and
+/// so subsumes naked and hidden singles.
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct AllDifferent<V> {
+    variables: Vec<V>,
+}
+
+impl<V> AllDifferent<V> {
+    pub fn new(variables: Vec<V>) -> AllDifferent<V> {
+        AllDifferent {
+            variables: variables,
+        }
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for AllDifferent<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "alldiff{:?}", self.variables)
+    }
+}
+
+impl<'a, P: CSP<'a>> Constraint<'a, P> for AllDifferent<P::Variable> {
+    fn is_satisfied(&self, env: &HashMap<P::Variable, P::Value>) -> bool {
+        let mut seen = HashSet::new();
+        self.variables.iter()
+            .filter_map(|variable| env.get(variable))
+            .all(|value| seen.insert(value))
+    }
+
+    fn prune(
+        &self,
+        domains: &mut Domains<P::Variable, P::Value>,
+        _: &HashMap<P::Variable, P::Value>,
+        variable: &P::Variable,
+        value: &P::Value
+    ) -> usize {
+        if !self.variables.contains(variable) {
+            return 0;
+        }
+        let mut removed = 0;
+        for other in &self.variables {
+            if other != variable && domains.remove(other, value) {
+                removed += 1;
+            }
+        }
+        let present: Vec<P::Variable> = self.variables.iter()
+            .filter(|other| *other != variable && domains.contains(other))
+            .cloned()
+            .collect();
+        return removed + filter(domains, &present);
+    }
+
+    fn scope(&self) -> Vec<P::Variable> {
+        self.variables.clone()
+    }
+
+    /// Filters all the variables with a single matching, instead of one for
+    /// every variable to revise.
+    fn revise(&self, domains: &mut Domains<P::Variable, P::Value>, variables: &[P::Variable]) -> Vec<P::Variable> {
+        if !variables.iter().any(|variable| domains.contains(variable)) {
+            return Vec::new();
+        }
+        let present: Vec<P::Variable> = self.variables.iter()
+            .filter(|other| domains.contains(other))
+            .cloned()
+            .collect();
+        let sizes: Vec<usize> = present.iter().map(|other| domains.get(other).unwrap().len()).collect();
+        filter(domains, &present);
+        return present.into_iter().zip(sizes)
+            .filter(|(other, size)| domains.get(other).unwrap().len() < *size)
+            .map(|(other, _)| other)
+            .collect();
+    }
+}
+
+/// Removes the values of the variables without support, returning the number
+/// of removed values. When the variables cannot all take different values,
+/// the domain of one of them is wiped out.
+fn filter<V: Eq + Hash + Clone, T: Eq + Hash + Clone>(
+    domains: &mut Domains<V, T>,
+    variables: &[V]
+) -> usize {
+    let unsupported = {
+        let variable_domains: Vec<&HashSet<T>> = variables.iter()
+            .map(|variable| domains.get(variable).unwrap())
+            .collect();
+        unsupported(&variable_domains)
+    };
+    let mut removed = 0;
+    match unsupported {
+        Some(unsupported) => {
+            for (variable, values) in variables.iter().zip(unsupported) {
+                for value in values {
+                    if domains.remove(variable, &value) {
+                        removed += 1;
+                    }
+                }
+            }
+        },
+        None => {
+            let variable = variables.first().unwrap();
+            let values: Vec<T> = domains.get(variable).into_iter().flatten().cloned().collect();
+            for value in values {
+                if domains.remove(variable, &value) {
+                    removed += 1;
+                }
+            }
+        }
+    }
+    return removed;
+}
+
+/// The values of every domain which no maximum matching of the variables to
+/// their values uses, or `None` if no matching covers all the variables.
+///
+/// With the edges of a maximum matching going from the variables to the
+/// values and the other edges going back, an edge outside the matching is a
+/// part of some other maximum matching exactly when it lies on a cycle, i.e.
+/// within a strongly connected component, or on a path from a free value.
+fn unsupported<T: Eq + Hash + Clone>(domains: &[&HashSet<T>]) -> Option<Vec<Vec<T>>> {
+    let mut values: Vec<T> = Vec::new();
+    let mut indices: HashMap<T, usize> = HashMap::new();
+    let adjacency: Vec<Vec<usize>> = domains.iter()
+        .map(|domain| domain.iter()
+            .map(|value| *indices.entry(value.clone()).or_insert_with(|| {
+                values.push(value.clone());
+                values.len() - 1
+            }))
+            .collect())
+        .collect();
+    let size = domains.len();
+
+    // the matching, by augmenting paths
+    fn augment(
+        variable: usize, adjacency: &[Vec<usize>], visited: &mut Vec<bool>,
+        matched_values: &mut Vec<Option<usize>>, matched_variables: &mut Vec<Option<usize>>
+    ) -> bool {
+        for value in &adjacency[variable] {
+            if visited[*value] {
+                continue;
+            }
+            visited[*value] = true;
+            let free = match matched_variables[*value] {
+                None => true,
+                Some(other) => augment(other, adjacency, visited, matched_values, matched_variables)
+            };
+            if free {
+                matched_values[variable] = Some(*value);
+                matched_variables[*value] = Some(variable);
+                return true;
+            }
+        }
+        return false;
+    }
+
+    let mut matched_values: Vec<Option<usize>> = vec![None; size];
+    let mut matched_variables: Vec<Option<usize>> = vec![None; values.len()];
+    for variable in 0..size {
+        let mut visited = vec![false; values.len()];
+        if !augment(variable, &adjacency, &mut visited, &mut matched_values, &mut matched_variables) {
+            return None;
+        }
+    }
+
+    // the variables are the nodes `0..size` and the values the nodes after them
+    let mut edges: Vec<Vec<usize>> = vec![Vec::new(); size + values.len()];
+    for (variable, domain) in adjacency.iter().enumerate() {
+        for value in domain {
+            if matched_values[variable] == Some(*value) {
+                edges[variable].push(size + value);
+            } else {
+                edges[size + value].push(variable);
+            }
+        }
+    }
+
+    let mut reachable = vec![false; size + values.len()];
+    let mut stack: Vec<usize> = (0..values.len())
+        .filter(|value| matched_variables[*value].is_none())
+        .map(|value| size + value)
+        .collect();
+    while let Some(node) = stack.pop() {
+        if !reachable[node] {
+            reachable[node] = true;
+            stack.extend(edges[node].iter().cloned());
+        }
+    }
+
+    let components = strongly_connected_components(&edges);
+    return Some(adjacency.iter().enumerate()
+        .map(|(variable, domain)| domain.iter()
+            .filter(|value| {
+                let node = size + **value;
+                matched_values[variable] != Some(**value)
+                    && !reachable[node]
+                    && components[variable] != components[node]
+            })
+            .map(|value| values[*value].clone())
+            .collect())
+        .collect());
+}
+
+/// The component of every node, by Tarjan's algorithm.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<usize> {
+    struct Tarjan<'e> {
+        edges: &'e [Vec<usize>],
+        index: Vec<Option<usize>>,
+        lowlink: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next_index: usize,
+        components: Vec<usize>,
+        next_component: usize,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = Some(self.next_index);
+            self.lowlink[node] = self.next_index;
+            self.next_index += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+            for next in self.edges[node].iter().cloned() {
+                match self.index[next] {
+                    None => {
+                        self.visit(next);
+                        self.lowlink[node] = self.lowlink[node].min(self.lowlink[next]);
+                    },
+                    Some(index) if self.on_stack[next] => {
+                        self.lowlink[node] = self.lowlink[node].min(index);
+                    },
+                    _ => {}
+                }
+            }
+            if Some(self.lowlink[node]) == self.index[node] {
+                while let Some(other) = self.stack.pop() {
+                    self.on_stack[other] = false;
+                    self.components[other] = self.next_component;
+                    if other == node {
+                        break;
+                    }
+                }
+                self.next_component += 1;
+            }
+        }
+    }
+
+    let size = edges.len();
+    let mut tarjan = Tarjan {
+        edges: edges,
+        index: vec![None; size],
+        lowlink: vec![0; size],
+        on_stack: vec![false; size],
+        stack: Vec::new(),
+        next_index: 0,
+        components: vec![0; size],
+        next_component: 0,
+    };
+    for node in 0..size {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+    return tarjan.components;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn domain(values: &[u8]) -> HashSet<u8> {
+        values.iter().cloned().collect()
+    }
+
+    fn sorted(mut values: Vec<u8>) -> Vec<u8> {
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn test_unsupported() {
+        // 1 and 2 are taken by the first two variables, leaving 3 to the last
+        let (a, b, c) = (domain(&[1, 2]), domain(&[1, 2]), domain(&[1, 2, 3]));
+        let removed = unsupported(&[&a, &b, &c]).unwrap();
+        assert!(removed[0].is_empty());
+        assert!(removed[1].is_empty());
+        assert_eq!(sorted(removed[2].clone()), vec![1, 2]);
+
+        // with a value to spare every value is a part of some matching, so
+        // nothing is removed
+        let (a, b, c) = (domain(&[1, 2, 3]), domain(&[1, 2, 3]), domain(&[1, 2, 3, 4]));
+        let removed = unsupported(&[&a, &b, &c]).unwrap();
+        assert!(removed.iter().all(|values| values.is_empty()));
+
+        let (a, b, c) = (domain(&[1, 2]), domain(&[1, 2]), domain(&[1, 2]));
+        assert!(unsupported(&[&a, &b, &c]).is_none());
+    }
+
+    #[test]
+    fn test_hidden_single() {
+        // every value is needed, and 4 fits only the last variable
+        let (a, b, c, d) = (domain(&[1, 2, 3]), domain(&[1, 2, 3]), domain(&[1, 2, 3]), domain(&[1, 2, 3, 4]));
+        let removed = unsupported(&[&a, &b, &c, &d]).unwrap();
+        assert_eq!(sorted(removed[3].clone()), vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+enum Change<V, T> {
+    Removed(V, T),
+    Hidden(V, HashSet<T>),
+}
+
+/// The domains of the variables shared by the whole search, which records
+/// every change on a trail instead of being cloned at every node, so that the
+/// changes made below a node can be undone when backtracking.
+pub struct Domains<V, T> {
+    domains: HashMap<V, HashSet<T>>,
+    trail: Vec<Change<V, T>>,
+}
+
+impl<V: Eq + Hash + Clone, T: Eq + Hash + Clone> Domains<V, T> {
+    pub fn new(domains: HashMap<V, HashSet<T>>) -> Domains<V, T> {
+        Domains {
+            domains: domains,
+            trail: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, variable: &V) -> Option<&HashSet<T>> {
+        self.domains.get(variable)
+    }
+
+    pub fn contains(&self, variable: &V) -> bool {
+        self.domains.contains_key(variable)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&V, &HashSet<T>)> {
+        self.domains.iter()
+    }
+
+    pub fn is_wiped_out(&self) -> bool {
+        self.domains.values().any(|domain| domain.is_empty())
+    }
+
+    /// Removes the value from the domain of the variable, returning whether
+    /// it was there.
+    pub fn remove(&mut self, variable: &V, value: &T) -> bool {
+        let removed = self.domains.get_mut(variable)
+            .is_some_and(|domain| domain.remove(value));
+        if removed {
+            self.trail.push(Change::Removed(variable.clone(), value.clone()));
+        }
+        return removed;
+    }
+
+    /// Removes the values other than `value` from the domain of the variable.
+    pub fn assign(&mut self, variable: &V, value: &T) {
+        if let Some(domain) = self.domains.get_mut(variable) {
+            for other in domain.iter() {
+                if other != value {
+                    self.trail.push(Change::Removed(variable.clone(), other.clone()));
+                }
+            }
+            domain.retain(|other| other == value);
+        }
+    }
+
+    /// Takes the variable out of the domains, e.g. once it is assigned.
+    pub fn hide(&mut self, variable: &V) {
+        if let Some(domain) = self.domains.remove(variable) {
+            self.trail.push(Change::Hidden(variable.clone(), domain));
+        }
+    }
+
+    /// The point on the trail to undo the later changes back to.
+    pub fn mark(&self) -> usize {
+        self.trail.len()
+    }
+
+    pub fn undo(&mut self, mark: usize) {
+        while self.trail.len() > mark {
+            match self.trail.pop().unwrap() {
+                Change::Removed(variable, value) => {
+                    self.domains.get_mut(&variable).unwrap().insert(value);
+                },
+                Change::Hidden(variable, domain) => {
+                    self.domains.insert(variable, domain);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo() {
+        let initial: HashMap<u8, HashSet<u8>> = (0..3)
+            .map(|variable| (variable, (0..4).collect()))
+            .collect();
+        let mut domains = Domains::new(initial.clone());
+        domains.remove(&0, &1);
+        let mark = domains.mark();
+        assert!(!domains.remove(&0, &1));
+        domains.hide(&1);
+        domains.assign(&2, &3);
+        domains.remove(&2, &3);
+        assert!(!domains.contains(&1));
+        assert!(domains.is_wiped_out());
+
+        domains.undo(mark);
+        assert!(!domains.is_wiped_out());
+        assert_eq!(domains.get(&0).unwrap().len(), 3);
+        assert_eq!(domains.get(&1), initial.get(&1));
+        assert_eq!(domains.get(&2), initial.get(&2));
+        domains.undo(0);
+        assert_eq!(domains.domains, initial);
+    }
+}
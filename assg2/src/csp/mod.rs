@@ -1,4 +1,9 @@
 pub mod solvers;
+mod domains;
+mod all_different;
+
+pub use domains::Domains;
+pub use all_different::AllDifferent;
 
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
@@ -7,12 +12,78 @@ use std::fmt::Debug;
 pub trait Constraint<'a, P: CSP<'a>> {
     fn is_satisfied(&self, env: &HashMap<P::Variable, P::Value>) -> bool;
 
+    /// Removes the values of the other variables in the scope which conflict
+    /// with assigning `value` to `variable` on top of the `assignments`,
+    /// returning the number of removed values.
     fn prune(
         &self,
-        domains: &mut HashMap<P::Variable, HashSet<P::Value>>,
+        domains: &mut Domains<P::Variable, P::Value>,
+        assignments: &HashMap<P::Variable, P::Value>,
         variable: &P::Variable,
         value: &P::Value
     ) -> usize;
+
+    /// The variables the constraint is over.
+    fn scope(&self) -> Vec<P::Variable>;
+
+    /// Removes the values of `variables` which do not satisfy the constraint
+    /// with any values of the other variables in its scope, returning the
+    /// variables whose domains shrank. The variables missing from `domains`
+    /// are treated as unconstrained. The constraints filtering all their
+    /// variables at once may shrink the other variables of the scope as well.
+    fn revise(&self, domains: &mut Domains<P::Variable, P::Value>, variables: &[P::Variable]) -> Vec<P::Variable> {
+        let mut shrunk = Vec::new();
+        for variable in variables {
+            let others: Vec<P::Variable> = self.scope().into_iter()
+                .filter(|other| other != variable && domains.contains(other))
+                .collect();
+            let unsupported: Vec<P::Value> = match domains.get(variable) {
+                Some(domain) => domain.iter()
+                    .filter(|value| {
+                        let mut env = HashMap::new();
+                        env.insert(variable.clone(), (*value).clone());
+                        !has_support(self, domains, &others, &mut env)
+                    })
+                    .cloned()
+                    .collect(),
+                None => continue
+            };
+            if unsupported.is_empty() {
+                continue;
+            }
+            for value in &unsupported {
+                domains.remove(variable, value);
+            }
+            shrunk.push(variable.clone());
+            if domains.get(variable).unwrap().is_empty() {
+                break;
+            }
+        }
+        return shrunk;
+    }
+}
+
+/// Checks whether the values of the variables in `env` can be extended to the
+/// remaining variables so that the constraint is satisfied.
+fn has_support<'a, P: CSP<'a>, C: Constraint<'a, P> + ?Sized>(
+    constraint: &C,
+    domains: &Domains<P::Variable, P::Value>,
+    remaining: &[P::Variable],
+    env: &mut HashMap<P::Variable, P::Value>
+) -> bool {
+    match remaining.split_first() {
+        Some((variable, rest)) => {
+            for value in domains.get(variable).unwrap() {
+                env.insert(variable.clone(), value.clone());
+                if has_support(constraint, domains, rest, env) {
+                    return true;
+                }
+            }
+            env.remove(variable);
+            return false;
+        },
+        None => constraint.is_satisfied(env)
+    }
 }
 
 pub trait Solution<'a, P: CSP<'a>> {
@@ -1,87 +1,72 @@
-use super::{CSP, Solution};
+use super::{CSP, Domains, Solution};
+use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
-
-pub struct Order<T> {
-    source: Vec<T>,
-    forward: usize,
-    backward: usize,
-}
-
-impl<T> Order<T> {
-    pub fn new(source: Vec<T>) -> Order<T> {
-        let size = source.len();
-        Order {
-            source: source,
-            forward: 0,
-            backward: size - 1,
-        }
-    }
-}
-
-impl<T: Clone> Iterator for Order<T> {
-    type Item = T;
-
-    fn next(&mut self) -> Option<T> {
-        let result = self.source.get(self.forward);
-        self.forward += 1;
-        return result.cloned();
-    }
-}
-
-impl<T: Clone> DoubleEndedIterator for Order<T> {
-    fn next_back(&mut self) -> Option<T> {
-        let result = self.source.get(self.backward);
-        self.backward -= 1;
-        return result.cloned();
-    }
-}
+use std::hash::Hash;
 
 #[derive(Copy, Clone)]
 pub enum VariableSelector {
     OrderOfDefinition,
     MostConstrainedVariable,
     Random,
+    /// The variable in the most constraints with unassigned variables.
+    Degree,
+    /// The most constrained variable, with ties broken by the degree.
+    MostConstrainedVariableDegree,
+    /// The smallest ratio of the domain size to the degree.
+    DomainOverDegree,
+    /// The smallest ratio of the domain size to the degree, where every
+    /// constraint counts as many times as it has failed.
+    DomainOverWeightedDegree,
 }
 
 impl VariableSelector {
-    pub fn variables<'a, P: CSP<'a>>(&self, domains: HashMap<P::Variable, HashSet<P::Value>>)
-        -> Order<P::Variable>
+    /// Picks the unassigned variable to assign next, or `None` once every
+    /// variable is assigned. Heuristics learning over the search keep their
+    /// state in the graph, like the weights of `DomainOverWeightedDegree`.
+    pub fn select<'a, P: CSP<'a>>(
+        &self,
+        domains: &Domains<P::Variable, P::Value>,
+        assignments: &HashMap<P::Variable, P::Value>,
+        graph: &ConstraintGraph<'a, P>
+    ) -> Option<P::Variable>
     {
-        match self {
-            Self::OrderOfDefinition => {
-                use itertools::Itertools;
+        use std::cmp::Reverse;
 
-                let variables = domains.iter()
-                    .sorted_by(|(va, _), (vb, _)| Ord::cmp(&va, &vb))
-                    .map(|(a, _)| a.clone())
-                    .collect();
-                Order::new(variables)
-            }
-            Self::MostConstrainedVariable => {
-                use itertools::Itertools;
-
-                let variables = domains.iter()
-                    .sorted_by(|(va, domain_a), (vb, domain_b)| {
-                        match Ord::cmp(&domain_a.len(), &domain_b.len()) {
-                            std::cmp::Ordering::Equal => Ord::cmp(&va, &vb),
-                            o => o
-                        }
-                    })
-                    .map(|(a, _)| a.clone())
-                    .collect();
-                Order::new(variables)
-            },
+        let unassigned = domains.iter()
+            .filter(|(variable, _)| !assignments.contains_key(variable));
+        let variable = match self {
+            Self::OrderOfDefinition => unassigned
+                .min_by_key(|(variable, _)| *variable),
+            Self::MostConstrainedVariable => unassigned
+                .min_by_key(|(variable, domain)| (domain.len(), *variable)),
             Self::Random => {
                 use rand::thread_rng;
-                use rand::seq::SliceRandom;
+                use rand::seq::IteratorRandom;
 
-                let mut variables: Vec<P::Variable> = domains.iter()
-                    .map(|(a, _)| a.clone())
-                    .collect();
-                variables.shuffle(&mut thread_rng());
-                Order::new(variables)
+                unassigned.choose(&mut thread_rng())
+            },
+            Self::Degree => unassigned
+                .min_by_key(|(variable, _)| (Reverse(graph.degree(variable, assignments, false)), *variable)),
+            Self::MostConstrainedVariableDegree => unassigned
+                .min_by_key(|(variable, domain)| {
+                    (domain.len(), Reverse(graph.degree(variable, assignments, false)), *variable)
+                }),
+            Self::DomainOverDegree | Self::DomainOverWeightedDegree => {
+                let weighted = matches!(self, Self::DomainOverWeightedDegree);
+                // the ratios are compared by cross-multiplying, and a variable
+                // without constraints counts as having one
+                unassigned
+                    .map(|(variable, domain)| {
+                        (variable, domain, graph.degree(variable, assignments, weighted).max(1))
+                    })
+                    .min_by(|(va, domain_a, degree_a), (vb, domain_b, degree_b)| {
+                        Ord::cmp(&(domain_a.len() * degree_b), &(domain_b.len() * degree_a))
+                            .then_with(|| Ord::cmp(va, vb))
+                    })
+                    .map(|(variable, domain, _)| (variable, domain))
             }
-        }
+        };
+        return variable.map(|(variable, _)| variable.clone());
     }
 }
 
@@ -94,235 +79,1007 @@ pub enum ValueSelector {
 }
 
 impl ValueSelector {
+    /// The values of the variable in the order they are tried, none if the
+    /// domain of the variable is empty.
     pub fn values<'a, P: CSP<'a>>(
         &self,
         variable: &P::Variable,
-        domains: &HashMap<P::Variable, HashSet<P::Value>>,
+        domains: &mut Domains<P::Variable, P::Value>,
         assignments: &HashMap<P::Variable, P::Value>,
-        constraints: &P::Constraints
-    ) -> Order<P::Value>
+        graph: &ConstraintGraph<'a, P>
+    ) -> Vec<P::Value>
     {
+        use itertools::Itertools;
+
+        let candidates: Vec<P::Value> = domains.get(variable).into_iter().flatten().cloned().collect();
         match self {
             Self::OrderOfDefinition => {
-                use itertools::Itertools;
-
-                let values = domains.get(variable).unwrap().iter()
-                    .map(|a| a.clone())
-                    .sorted_by(|a, b| Ord::cmp(&a, &b))
-                    .collect();
-                Order::new(values)
+                return candidates.into_iter().sorted().collect();
             },
             Self::LeastConstrainingValue => {
-                use itertools::Itertools;
                 use super::Constraint;
 
-                let values = domains.get(variable).unwrap().iter()
+                return candidates.into_iter()
                     .map(|value| -> (P::Value, usize) {
-                        let mut temp_domains = domains.clone();
+                        let mark = domains.mark();
                         let mut removed = 0;
-                        for constraint in constraints.clone().into_iter() {
-                            removed += constraint.prune(&mut temp_domains, &variable, &value);
-                        }
-                        return (value.clone(), removed);
-                    })
-                    .sorted_by(|(va, a), (vb, b)| {
-                        match Ord::cmp(&a, &b) {
-                            std::cmp::Ordering::Equal => Ord::cmp(&va, &vb),
-                            o => o
+                        for constraint in graph.over(variable) {
+                            removed += constraint.prune(domains, assignments, variable, &value);
                         }
+                        domains.undo(mark);
+                        return (value, removed);
                     })
-                    .map(|(x, _)| x)
+                    .sorted_by(|(va, a), (vb, b)| Ord::cmp(a, b).then_with(|| Ord::cmp(va, vb)))
+                    .map(|(value, _)| value)
                     .collect();
-                Order::new(values)
             },
             Self::Random => {
                 use rand::thread_rng;
                 use rand::seq::SliceRandom;
 
-                let mut values: Vec<P::Value> = domains.get(variable).unwrap().iter()
-                    .map(|a| a.clone())
-                    .collect();
+                let mut values = candidates;
                 values.shuffle(&mut thread_rng());
-                Order::new(values)
+                return values;
             },
             Self::LeastOccuringValue => {
-                use itertools::Itertools;
-                
-                let values: Vec<P::Value> = domains.get(variable).unwrap().iter()
+                return candidates.into_iter()
                     .map(|value| -> (P::Value, usize) {
-                        let mut occurences = 0;
-                        for v in assignments.values() {
-                            if v == value {
-                                occurences += 1;
-                            }
-                        }
-                        return (value.clone(), occurences);
-                    })
-                    .sorted_by(|(va, a), (vb, b)| {
-                        match Ord::cmp(&a, &b) {
-                            std::cmp::Ordering::Equal => Ord::cmp(&va, &vb),
-                            o => o
-                        }
+                        let occurences = assignments.values().filter(|other| **other == value).count();
+                        return (value, occurences);
                     })
-                    .map(|(x, _)| x)
+                    .sorted_by(|(va, a), (vb, b)| Ord::cmp(a, b).then_with(|| Ord::cmp(va, vb)))
+                    .map(|(value, _)| value)
                     .collect();
-                Order::new(values)
             }
         }
     }
 }
 
-use std::time::Instant;
+/// How many solutions to look for before stopping the search.
+#[derive(Copy, Clone)]
+pub enum Limit {
+    All,
+    First(usize),
+}
+
+impl Limit {
+    fn reached(&self, solutions_found: usize) -> bool {
+        match self {
+            Self::All => false,
+            Self::First(count) => solutions_found >= *count,
+        }
+    }
+}
+
+use std::time::{Duration, Instant};
 
-#[derive(Clone)]
-struct Statistics {
-    visited_nodes: usize,
-    backtracks: usize,
-    solutions_found: usize,
+/// Statistics of a single search.
+#[derive(Clone, Debug)]
+pub struct SearchReport {
+    pub visited_nodes: usize,
+    pub backtracks: usize,
+    pub solutions_found: usize,
+    pub first_solution_time: Option<Duration>,
+    pub total_time: Duration,
+    /// The number of visited nodes at every depth, i.e. after how many
+    /// assignments the nodes were visited.
+    pub nodes_per_depth: Vec<usize>,
     start_time: Instant,
 }
 
-pub fn backtracking<'a, P: CSP<'a>>(
+impl SearchReport {
+    fn new() -> SearchReport {
+        SearchReport {
+            visited_nodes: 0,
+            backtracks: 0,
+            solutions_found: 0,
+            first_solution_time: None,
+            total_time: Duration::default(),
+            nodes_per_depth: Vec::new(),
+            start_time: Instant::now(),
+        }
+    }
+
+    fn visit(&mut self, depth: usize) {
+        self.visited_nodes += 1;
+        if self.nodes_per_depth.len() <= depth {
+            self.nodes_per_depth.resize(depth + 1, 0);
+        }
+        self.nodes_per_depth[depth] += 1;
+    }
+
+    /// Records a solution found at the current node, which counts as a
+    /// backtrack as the search goes on.
+    fn solution(&mut self) {
+        use log::info;
+
+        if self.solutions_found == 0 {
+            let time = self.start_time.elapsed();
+            self.first_solution_time = Some(time);
+            info!(
+                "First solution found in {:.2?}, after {} visited nodes and {} backtracks",
+                time, self.visited_nodes, self.backtracks
+            );
+        }
+        self.solutions_found += 1;
+        self.backtracks += 1;
+    }
+
+    /// Stops the clock and logs the statistics. A search which `exhausted`
+    /// the tree did not backtrack out of its root.
+    fn finish(mut self, exhausted: bool) -> SearchReport {
+        use log::{info, warn};
+
+        if exhausted {
+            self.backtracks -= 1; // function exit
+        }
+        self.total_time = self.start_time.elapsed();
+        info!(
+            "Finished the algorithm in {:.2?}, after {} visited nodes and {} backtracks",
+            self.total_time, self.visited_nodes, self.backtracks
+        );
+        match self.solutions_found {
+            0 => warn!("0 solutions found"),
+            1 => info!("1 solution found"),
+            n => info!("{} solutions found", n)
+        }
+        return self;
+    }
+
+    pub fn csv_header() -> &'static str {
+        "visited_nodes,backtracks,solutions_found,first_solution_time,total_time,nodes_per_depth"
+    }
+
+    /// A single CSV row, with the times in seconds and the nodes per depth
+    /// separated by spaces.
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.visited_nodes, self.backtracks, self.solutions_found,
+            self.first_solution_time.map_or(String::new(), |time| time.as_secs_f64().to_string()),
+            self.total_time.as_secs_f64(),
+            self.nodes_per_depth.iter().map(|nodes| nodes.to_string()).collect::<Vec<_>>().join(" ")
+        )
+    }
+
+    /// A JSON object, with the times in seconds.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"visited_nodes\":{},\"backtracks\":{},\"solutions_found\":{},\"first_solution_time\":{},\"total_time\":{},\"nodes_per_depth\":[{}]}}",
+            self.visited_nodes, self.backtracks, self.solutions_found,
+            self.first_solution_time.map_or("null".to_owned(), |time| time.as_secs_f64().to_string()),
+            self.total_time.as_secs_f64(),
+            self.nodes_per_depth.iter().map(|nodes| nodes.to_string()).collect::<Vec<_>>().join(",")
+        )
+    }
+}
+
+/// The constraints along with the indices of the constraints over every
+/// variable, so that only the constraints affected by an assignment or a
+/// shrunk domain are looked at.
+pub struct ConstraintGraph<'a, P: CSP<'a>> {
+    constraints: Vec<P::Constraint>,
+    index: HashMap<P::Variable, Vec<usize>>,
+    /// How many times every constraint has failed, plus one, for the
+    /// weighted degree.
+    weights: Vec<Cell<usize>>,
+}
+
+impl<'a, P: CSP<'a>> ConstraintGraph<'a, P> {
+    pub fn new(constraints: P::Constraints) -> ConstraintGraph<'a, P> {
+        use super::Constraint;
+
+        let constraints: Vec<P::Constraint> = constraints.into_iter().collect();
+        let mut index: HashMap<P::Variable, Vec<usize>> = HashMap::new();
+        for (i, constraint) in constraints.iter().enumerate() {
+            for variable in constraint.scope() {
+                index.entry(variable).or_default().push(i);
+            }
+        }
+        let weights = constraints.iter().map(|_| Cell::new(1)).collect();
+        ConstraintGraph {
+            constraints: constraints,
+            index: index,
+            weights: weights,
+        }
+    }
+
+    /// The constraints with the variable in their scope.
+    pub fn over<'g>(&'g self, variable: &P::Variable) -> impl Iterator<Item = &'g P::Constraint> + 'g {
+        self.index.get(variable).into_iter().flatten()
+            .map(move |constraint| &self.constraints[*constraint])
+    }
+
+    /// The number of the constraints over the variable with another
+    /// unassigned variable in their scope, or the sum of their weights if
+    /// `weighted`.
+    fn degree(&self, variable: &P::Variable, assignments: &HashMap<P::Variable, P::Value>, weighted: bool) -> usize {
+        use super::Constraint;
+
+        self.index.get(variable).into_iter().flatten()
+            .filter(|constraint| self.constraints[**constraint].scope().iter()
+                .any(|other| other != variable && !assignments.contains_key(other)))
+            .map(|constraint| if weighted { self.weights[*constraint].get() } else { 1 })
+            .sum()
+    }
+
+    fn fail(&self, constraint: usize) {
+        self.weights[constraint].set(self.weights[constraint].get() + 1);
+    }
+
+    /// Checks the constraints over the assigned variable, stopping at the
+    /// first violated one.
+    fn check(&self, variable: &P::Variable, assignments: &HashMap<P::Variable, P::Value>) -> bool {
+        use super::Constraint;
+
+        for constraint in self.index.get(variable).into_iter().flatten() {
+            if !self.constraints[*constraint].is_satisfied(assignments) {
+                self.fail(*constraint);
+                return false;
+            }
+        }
+        return true;
+    }
+
+    /// Prunes the domains with the constraints over the assigned variable,
+    /// stopping at the first one wiping a domain out.
+    fn prune(
+        &self,
+        domains: &mut Domains<P::Variable, P::Value>,
+        assignments: &HashMap<P::Variable, P::Value>,
+        variable: &P::Variable,
+        value: &P::Value
+    ) -> bool {
+        use super::Constraint;
+
+        for constraint in self.index.get(variable).into_iter().flatten() {
+            let constraint_ref = &self.constraints[*constraint];
+            if constraint_ref.prune(domains, assignments, variable, value) > 0 && constraint_ref.scope().iter()
+                .any(|other| domains.get(other).is_some_and(|domain| domain.is_empty()))
+            {
+                self.fail(*constraint);
+                return false;
+            }
+        }
+        return true;
+    }
+}
+
+/// Restricts the domains to an assignment, already in `assignments`, and
+/// checks or propagates it. Returns `false` if the assignment fails.
+type Propagation<'a, P> = fn(
+    &ConstraintGraph<'a, P>,
+    &mut Domains<<P as CSP<'a>>::Variable, <P as CSP<'a>>::Value>,
+    &HashMap<<P as CSP<'a>>::Variable, <P as CSP<'a>>::Value>,
+    &<P as CSP<'a>>::Variable,
+    &<P as CSP<'a>>::Value
+) -> bool;
+
+/// The chronological backtracking shared by the methods differing only in
+/// what they do after every assignment. Returns whether to keep searching.
+#[allow(clippy::too_many_arguments)]
+fn backtrack<'a, P: CSP<'a>>(
+    problem: &'a P, variable_selector: VariableSelector, value_selector: ValueSelector,
+    propagate: Propagation<'a, P>,
+    domains: &mut Domains<P::Variable, P::Value>, assignments: &mut HashMap<P::Variable, P::Value>,
+    graph: &ConstraintGraph<'a, P>, statistics: &mut SearchReport,
+    on_solution: &mut dyn FnMut(P::Solution) -> bool
+) -> bool {
+    if let Some(variable) = variable_selector.select::<P>(domains, assignments, graph) {
+        for value in value_selector.values::<P>(&variable, domains, assignments, graph) {
+            statistics.visit(assignments.len());
+            let mark = domains.mark();
+            assignments.insert(variable.clone(), value.clone());
+            let keep_searching = if propagate(graph, domains, assignments, &variable, &value) {
+                backtrack::<P>(
+                    problem, variable_selector, value_selector, propagate, domains,
+                    assignments, graph, statistics, on_solution
+                )
+            } else {
+                statistics.backtracks += 1;
+                true
+            };
+            assignments.remove(&variable);
+            domains.undo(mark);
+            if !keep_searching {
+                return false;
+            }
+        }
+        statistics.backtracks += 1;
+        return true;
+    } else {
+        statistics.solution();
+        return on_solution(P::Solution::construct(problem, assignments));
+    }
+}
+
+/// Calls `on_solution` with every solution as soon as it is found, until
+/// the limit is reached, and returns the statistics of the search.
+pub fn backtracking<'a, P: CSP<'a>, F: FnMut(P::Solution)>(
     problem: &'a P,
     variable_selector: VariableSelector,
-    value_selector: ValueSelector
-) -> HashSet<P::Solution> {
-    use log::{info, warn};
+    value_selector: ValueSelector,
+    limit: Limit,
+    mut on_solution: F
+) -> SearchReport {
+    use log::info;
+
+    let mut statistics = SearchReport::new();
+    info!("Initialized the backtracking method");
+    let mut found = 0;
+    let finished = backtrack(
+        problem, variable_selector, value_selector,
+        |graph, domains, assignments, variable, _| {
+            domains.hide(variable);
+            graph.check(variable, assignments)
+        },
+        &mut Domains::new(problem.domains()), &mut HashMap::new(),
+        &ConstraintGraph::new(problem.constraints()), &mut statistics, &mut |solution| {
+            on_solution(solution);
+            found += 1;
+            !limit.reached(found)
+        }
+    );
+    return statistics.finish(finished);
+}
+
+pub fn forward_checking<'a, P: CSP<'a>, F: FnMut(P::Solution)>(
+    problem: &'a P,
+    variable_selector: VariableSelector,
+    value_selector: ValueSelector,
+    limit: Limit,
+    mut on_solution: F
+) -> SearchReport {
+    use log::info;
+
+    let mut statistics = SearchReport::new();
+    info!("Initialized the forward-checking method");
+    let mut domains = Domains::new(problem.domains());
+    let mut found = 0;
+    // the constraints only prune the domains they are over, so a domain
+    // empty from the start is caught here
+    let finished = !domains.is_wiped_out() && backtrack(
+        problem, variable_selector, value_selector,
+        |graph, domains, assignments, variable, value| {
+            domains.hide(variable);
+            graph.prune(domains, assignments, variable, value)
+        },
+        &mut domains, &mut HashMap::new(),
+        &ConstraintGraph::new(problem.constraints()), &mut statistics, &mut |solution| {
+            on_solution(solution);
+            found += 1;
+            !limit.reached(found)
+        }
+    );
+    return statistics.finish(finished);
+}
+
+/// Makes the domains arc consistent with AC-3, starting from the arcs towards
+/// the variables constrained together with the `changed` ones. The arcs of a
+/// constraint waiting in the queue are revised together. Returns `false` as
+/// soon as a domain is wiped out.
+fn arc_consistency<'a, P: CSP<'a>>(
+    graph: &ConstraintGraph<'a, P>,
+    domains: &mut Domains<P::Variable, P::Value>,
+    changed: Vec<P::Variable>
+) -> bool {
     use super::Constraint;
+    use std::collections::VecDeque;
 
-    fn backtrack<'a, P: CSP<'a>>(
-        problem: &'a P, variable_selector: VariableSelector, value_selector: ValueSelector,
-        domains: HashMap<P::Variable, HashSet<P::Value>>, assignments: HashMap<P::Variable, P::Value>,
-        constraints: &P::Constraints, statistics: &mut Statistics
-    ) -> HashSet<P::Solution> {
-        if let Some(variable) = variable_selector.variables::<P>(domains.clone()).next() {
-            let mut solutions = HashSet::new();
-            for value in value_selector.values::<P>(&variable, &domains, &assignments, &constraints).rev() {
-                statistics.visited_nodes += 1;
-                let mut new_domains = domains.clone();
-                let mut new_assignments = assignments.clone();
-                new_domains.remove(&variable);
-                new_assignments.insert(variable.clone(), value.clone());
-                if constraints.clone().into_iter().all(|constraint| {
-                    constraint.is_satisfied(&new_assignments)
-                }) {
-                    solutions.extend(backtrack::<P>(
-                        problem, variable_selector, value_selector, new_domains,
-                        new_assignments, constraints, statistics
-                    ));
-                } else {
-                    statistics.backtracks += 1;
+    // the arcs of the constraints over `variable` towards its neighbors
+    fn enqueue<'a, P: CSP<'a>>(
+        graph: &ConstraintGraph<'a, P>, variable: &P::Variable, except: Option<usize>,
+        queue: &mut VecDeque<usize>, queued: &mut HashMap<usize, Vec<P::Variable>>
+    ) {
+        for constraint in graph.index.get(variable).into_iter().flatten() {
+            if Some(*constraint) == except {
+                continue;
+            }
+            let targets = queued.entry(*constraint).or_insert_with(|| {
+                queue.push_back(*constraint);
+                Vec::new()
+            });
+            for other in graph.constraints[*constraint].scope() {
+                if other != *variable && !targets.contains(&other) {
+                    targets.push(other);
                 }
             }
-            statistics.backtracks += 1;
-            return solutions;
-        } else {
-            if statistics.solutions_found == 0 {
-                info!(
-                    "First solution found in {:.2?}, after {} visited nodes and {} backtracks",
-                    statistics.start_time.elapsed(), statistics.visited_nodes, statistics.backtracks
-                );
+        }
+    }
+
+    let mut queue = VecDeque::new();
+    let mut queued = HashMap::new();
+    for variable in &changed {
+        enqueue(graph, variable, None, &mut queue, &mut queued);
+    }
+    while let Some(constraint) = queue.pop_front() {
+        let targets = queued.remove(&constraint).unwrap();
+        for variable in graph.constraints[constraint].revise(domains, &targets) {
+            if domains.get(&variable).unwrap().is_empty() {
+                graph.fail(constraint);
+                return false;
             }
-            statistics.solutions_found += 1;
-            statistics.backtracks += 1;
-            return [P::Solution::construct(problem, &assignments)].iter().cloned().collect();
+            enqueue(graph, &variable, Some(constraint), &mut queue, &mut queued);
         }
     }
+    return true;
+}
 
-    let mut statistics = Statistics {
-        visited_nodes: 0,
-        backtracks: 0,
-        solutions_found: 0,
-        start_time: Instant::now(),
-    };
-    info!("Initialized the backtracking method");
-    let solutions = backtrack(
-        problem, variable_selector, value_selector, problem.domains(),
-        HashMap::new(), &problem.constraints(), &mut statistics
-    );
-    statistics.backtracks -= 1; // function exit
-    info!(
-        "Finished the algorithm in {:.2?}, after {} visited nodes and {} backtracks",
-        statistics.start_time.elapsed(), statistics.visited_nodes, statistics.backtracks
+pub fn maintaining_arc_consistency<'a, P: CSP<'a>, F: FnMut(P::Solution)>(
+    problem: &'a P,
+    variable_selector: VariableSelector,
+    value_selector: ValueSelector,
+    limit: Limit,
+    mut on_solution: F
+) -> SearchReport {
+    use log::info;
+
+    let mut statistics = SearchReport::new();
+    let graph = ConstraintGraph::<P>::new(problem.constraints());
+    info!("Initialized the maintaining arc consistency method");
+    let mut domains = Domains::new(problem.domains());
+    let variables = domains.iter().map(|(variable, _)| variable.clone()).collect();
+    let mut found = 0;
+    let finished = arc_consistency(&graph, &mut domains, variables) && backtrack(
+        problem, variable_selector, value_selector,
+        // the domains of the assigned variables are kept as singletons, so
+        // that they keep supporting the values of their neighbors
+        |graph, domains, _, variable, value| {
+            domains.assign(variable, value);
+            arc_consistency(graph, domains, vec![variable.clone()])
+        },
+        &mut domains, &mut HashMap::new(), &graph, &mut statistics, &mut |solution| {
+            on_solution(solution);
+            found += 1;
+            !limit.reached(found)
+        }
     );
-    match solutions.len() {
-        0 => warn!("0 solutions found"),
-        1 => info!("1 solution found"),
-        n => info!("{} solutions found", n)
+    return statistics.finish(finished);
+}
+
+/// A combination of assignments which cannot be extended to a solution,
+/// learned when backjumping, and checked like any other constraint.
+#[derive(Clone, Debug)]
+pub struct Nogood<V, T> {
+    assignments: Vec<(V, T)>,
+}
+
+impl<'a, P: CSP<'a>> super::Constraint<'a, P> for Nogood<P::Variable, P::Value> {
+    fn is_satisfied(&self, env: &HashMap<P::Variable, P::Value>) -> bool {
+        !self.assignments.iter().all(|(variable, value)| env.get(variable) == Some(value))
+    }
+
+    /// Removes the value of the only variable of the nogood left unassigned
+    /// once all the others hold.
+    fn prune(
+        &self,
+        domains: &mut Domains<P::Variable, P::Value>,
+        assignments: &HashMap<P::Variable, P::Value>,
+        variable: &P::Variable,
+        value: &P::Value
+    ) -> usize {
+        let holds = |other: &P::Variable, other_value: &P::Value| match other == variable {
+            true => other_value == value,
+            false => assignments.get(other) == Some(other_value)
+        };
+        let mut left = self.assignments.iter().filter(|(other, other_value)| !holds(other, other_value));
+        match (left.next(), left.next()) {
+            (Some((other, other_value)), None) if !assignments.contains_key(other) => domains.remove(other, other_value) as usize,
+            _ => 0
+        }
+    }
+
+    fn scope(&self) -> Vec<P::Variable> {
+        self.assignments.iter().map(|(variable, _)| variable.clone()).collect()
+    }
+}
+
+/// The longest nogoods learned, as the longer ones are rarely violated and
+/// slow down every assignment.
+const MAX_NOGOOD_SIZE: usize = 8;
+
+/// The nogoods learned so far, indexed by the variables in their scope.
+struct Nogoods<V, T> {
+    nogoods: Vec<Nogood<V, T>>,
+    index: HashMap<V, Vec<usize>>,
+}
+
+impl<V: Eq + Hash + Clone, T: Eq + Hash + Clone> Nogoods<V, T> {
+    fn new() -> Nogoods<V, T> {
+        Nogoods {
+            nogoods: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    fn learn(&mut self, nogood: Nogood<V, T>) {
+        for (variable, _) in &nogood.assignments {
+            self.index.entry(variable.clone()).or_default().push(self.nogoods.len());
+        }
+        self.nogoods.push(nogood);
     }
-    return solutions;
 }
 
-pub fn forward_checking<'a, P: CSP<'a>>(
+/// The variables whose assignments a failure is blamed on, or `None` once the
+/// solution limit is reached.
+type Conflict<V> = Option<HashSet<V>>;
+
+/// Prunes the domains with the constraints over the assigned variable, and
+/// then with the learned nogoods over it, noting the variables to blame in the
+/// past of every pruned variable, which goes to `pruned` once for every noted
+/// variable. Returns the variable with its domain wiped out, if any.
+#[allow(clippy::too_many_arguments)]
+fn forward_check<'a, P: CSP<'a>>(
+    graph: &ConstraintGraph<'a, P>,
+    nogoods: Option<&Nogoods<P::Variable, P::Value>>,
+    domains: &mut Domains<P::Variable, P::Value>,
+    assignments: &HashMap<P::Variable, P::Value>,
+    variable: &P::Variable,
+    value: &P::Value,
+    past: &mut HashMap<P::Variable, Vec<P::Variable>>,
+    pruned: &mut Vec<P::Variable>
+) -> Option<P::Variable> {
+    use super::Constraint;
+
+    for constraint in graph.index.get(variable).into_iter().flatten() {
+        let sizes: Vec<(P::Variable, usize)> = graph.constraints[*constraint].scope().into_iter()
+            .filter_map(|other| domains.get(&other).map(|domain| domain.len()).map(|size| (other, size)))
+            .collect();
+        if graph.constraints[*constraint].prune(domains, assignments, variable, value) == 0 {
+            continue;
+        }
+        let scope = graph.constraints[*constraint].scope();
+        for (other, size) in sizes {
+            let remaining = domains.get(&other).unwrap().len();
+            if remaining < size {
+                // a constraint over more variables may prune with the values
+                // the others have left, so the variables which pruned them
+                // are to blame as well
+                let blamed: Vec<P::Variable> = scope.iter()
+                    .filter(|blamed| **blamed != other)
+                    .flat_map(|blamed| match domains.contains(blamed) {
+                        true => past.get(blamed).cloned().unwrap_or_default(),
+                        false => vec![blamed.clone()]
+                    })
+                    .collect();
+                let other_past = past.entry(other.clone()).or_default();
+                for blamed in blamed {
+                    if !other_past.contains(&blamed) {
+                        other_past.push(blamed);
+                        pruned.push(other.clone());
+                    }
+                }
+            }
+            if remaining == 0 {
+                graph.fail(*constraint);
+                return Some(other);
+            }
+        }
+    }
+    for nogood in nogoods.into_iter().flat_map(|nogoods| {
+        nogoods.index.get(variable).into_iter().flatten().map(move |nogood| &nogoods.nogoods[*nogood])
+    }) {
+        if Constraint::<P>::prune(nogood, domains, assignments, variable, value) == 0 {
+            continue;
+        }
+        // only the value of the single unassigned variable is removed, and
+        // the assignments of all the others are to blame
+        let other = nogood.assignments.iter()
+            .map(|(other, _)| other)
+            .find(|other| !assignments.contains_key(*other))
+            .unwrap();
+        let other_past = past.entry(other.clone()).or_default();
+        for (blamed, _) in &nogood.assignments {
+            if blamed != other && !other_past.contains(blamed) {
+                other_past.push(blamed.clone());
+                pruned.push(other.clone());
+            }
+        }
+        if domains.get(other).unwrap().is_empty() {
+            return Some(other.clone());
+        }
+    }
+    return None;
+}
+
+/// Forward-checking with conflict-directed backjumping. Every variable
+/// collects the earlier variables its values failed because of, and once the
+/// values run out the search jumps back to the latest of them instead of the
+/// previous variable. With `learn_nogoods` the assignments of these variables
+/// are also recorded as nogoods, which are checked after every assignment and
+/// prune the value of their last unassigned variable.
+pub fn conflict_directed_backjumping<'a, P: CSP<'a>, F: FnMut(P::Solution)>(
     problem: &'a P,
     variable_selector: VariableSelector,
-    value_selector: ValueSelector
-) -> HashSet<P::Solution> {
-    use log::{info, warn};
+    value_selector: ValueSelector,
+    limit: Limit,
+    learn_nogoods: bool,
+    mut on_solution: F
+) -> SearchReport {
+    use log::info;
     use super::Constraint;
 
+    /// The state of the search besides the domains and the assignments.
+    struct Backjumping<V, T> {
+        /// The variables to blame for the pruned values of every variable.
+        past: HashMap<V, Vec<V>>,
+        nogoods: Option<Nogoods<V, T>>,
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn backtrack<'a, P: CSP<'a>>(
         problem: &'a P, variable_selector: VariableSelector, value_selector: ValueSelector,
-        domains: HashMap<P::Variable, HashSet<P::Value>>, assignments: HashMap<P::Variable, P::Value>,
-        constraints: &P::Constraints, statistics: &mut Statistics
-    ) -> HashSet<P::Solution> {
-        if let Some(variable) = variable_selector.variables::<P>(domains.clone()).next() {
-            let mut solutions = HashSet::new();
-            for value in value_selector.values::<P>(&variable, &domains, &assignments, &constraints).rev() {
-                statistics.visited_nodes += 1;
-                let mut new_domains = domains.clone();
-                let mut new_assignments = assignments.clone();
-                new_domains.remove(&variable);
-                new_assignments.insert(variable.clone(), value.clone());
-                for constraint in constraints.clone().into_iter() {
-                    constraint.prune(&mut new_domains, &variable, &value);
+        domains: &mut Domains<P::Variable, P::Value>, assignments: &mut HashMap<P::Variable, P::Value>,
+        graph: &ConstraintGraph<'a, P>, state: &mut Backjumping<P::Variable, P::Value>,
+        statistics: &mut SearchReport, on_solution: &mut dyn FnMut(P::Solution) -> bool
+    ) -> Conflict<P::Variable> {
+        if let Some(variable) = variable_selector.select::<P>(domains, assignments, graph) {
+            let solutions_found = statistics.solutions_found;
+            let mut conflict = HashSet::new();
+            for value in value_selector.values::<P>(&variable, domains, assignments, graph) {
+                statistics.visit(assignments.len());
+                let mark = domains.mark();
+                let mut pruned = Vec::new();
+                domains.hide(&variable);
+                assignments.insert(variable.clone(), value.clone());
+                let violated = state.nogoods.as_ref().and_then(|nogoods| {
+                    nogoods.index.get(&variable).into_iter().flatten()
+                        .map(|nogood| &nogoods.nogoods[*nogood])
+                        .find(|nogood| !Constraint::<P>::is_satisfied(*nogood, assignments))
+                        .map(|nogood| Constraint::<P>::scope(nogood))
+                });
+                let outcome = match violated {
+                    Some(scope) => {
+                        statistics.backtracks += 1;
+                        Some(scope.into_iter().collect())
+                    },
+                    None => match forward_check(
+                        graph, state.nogoods.as_ref(), domains, assignments, &variable, &value,
+                        &mut state.past, &mut pruned
+                    ) {
+                        Some(wiped_out) => {
+                            statistics.backtracks += 1;
+                            Some(state.past[&wiped_out].iter().cloned().collect())
+                        },
+                        None => backtrack::<P>(
+                            problem, variable_selector, value_selector, domains,
+                            assignments, graph, state, statistics, on_solution
+                        )
+                    }
+                };
+                for other in &pruned {
+                    state.past.get_mut(other).unwrap().pop();
                 }
-                if new_domains.values().all(|domain| {
-                    !domain.is_empty()
-                }) {
-                    solutions.extend(backtrack::<P>(
-                        problem, variable_selector, value_selector, new_domains,
-                        new_assignments, constraints, statistics
-                    ));
-                } else {
-                    statistics.backtracks += 1;
+                assignments.remove(&variable);
+                domains.undo(mark);
+                match outcome {
+                    Some(mut blamed) => if blamed.remove(&variable) {
+                        conflict.extend(blamed);
+                    } else {
+                        // the variable is not to blame, so the other values
+                        // would fail the same way
+                        statistics.backtracks += 1;
+                        return Some(blamed);
+                    },
+                    None => return None
                 }
             }
             statistics.backtracks += 1;
-            return solutions;
+            conflict.extend(state.past.get(&variable).into_iter().flatten().cloned());
+            if let Some(nogoods) = &mut state.nogoods {
+                // the subtrees with solutions only blame all the earlier
+                // variables, which makes no use as a nogood
+                if statistics.solutions_found == solutions_found && conflict.len() <= MAX_NOGOOD_SIZE {
+                    nogoods.learn(Nogood {
+                        assignments: conflict.iter()
+                            .map(|blamed| (blamed.clone(), assignments[blamed].clone()))
+                            .collect(),
+                    });
+                }
+            }
+            return Some(conflict);
         } else {
-            if statistics.solutions_found == 0 {
-                info!(
-                    "First solution found in {:.2?}, after {} visited nodes and {} backtracks",
-                    statistics.start_time.elapsed(), statistics.visited_nodes, statistics.backtracks
-                );
+            statistics.solution();
+            if !on_solution(P::Solution::construct(problem, assignments)) {
+                return None;
             }
-            statistics.solutions_found += 1;
-            statistics.backtracks += 1;
-            return [P::Solution::construct(problem, &assignments)].iter().cloned().collect();
+            // looking for other solutions, so go back chronologically
+            return Some(assignments.keys().cloned().collect());
         }
     }
 
-    let mut statistics = Statistics {
-        visited_nodes: 0,
-        backtracks: 0,
-        solutions_found: 0,
-        start_time: Instant::now(),
+    let mut statistics = SearchReport::new();
+    info!("Initialized the conflict-directed backjumping method");
+    let mut domains = Domains::new(problem.domains());
+    let mut state = Backjumping {
+        past: HashMap::new(),
+        nogoods: if learn_nogoods { Some(Nogoods::new()) } else { None },
     };
-    info!("Initialized the forward-checking method");
-    let solutions = backtrack(
-        problem, variable_selector, value_selector, problem.domains(),
-        HashMap::new(), &problem.constraints(), &mut statistics
-    );
-    statistics.backtracks -= 1; // function exit
-    info!(
-        "Finished the algorithm in {:.2?}, after {} visited nodes and {} backtracks",
-        statistics.start_time.elapsed(), statistics.visited_nodes, statistics.backtracks
-    );
-    match solutions.len() {
-        0 => warn!("0 solutions found"),
-        1 => info!("1 solution found"),
-        n => info!("{} solutions found", n)
+    let mut found = 0;
+    let finished = !domains.is_wiped_out() && backtrack(
+        problem, variable_selector, value_selector, &mut domains,
+        &mut HashMap::new(), &ConstraintGraph::new(problem.constraints()), &mut state,
+        &mut statistics, &mut |solution| {
+            on_solution(solution);
+            found += 1;
+            !limit.reached(found)
+        }
+    ).is_some();
+    if let Some(nogoods) = &state.nogoods {
+        info!("Learned {} nogoods", nogoods.nogoods.len());
+    }
+    return statistics.finish(finished);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jolka::Jolka;
+    use crate::csp::Constraint;
+    use crate::sudoku::{self, Number, Rule, Sudoku};
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    fn collect<S: Eq + std::hash::Hash>(search: impl FnOnce(&mut dyn FnMut(S)) -> SearchReport) -> HashSet<S> {
+        let mut solutions = HashSet::new();
+        let report = search(&mut |solution| { solutions.insert(solution); });
+        assert_eq!(report.solutions_found, solutions.len());
+        assert_eq!(report.nodes_per_depth.iter().sum::<usize>(), report.visited_nodes);
+        assert_eq!(report.first_solution_time.is_some(), report.solutions_found > 0);
+        return solutions;
+    }
+
+    /// A sudoku counting the calls to `prune` of its constraints.
+    struct Counted {
+        sudoku: Sudoku,
+        calls: Rc<Cell<usize>>,
+    }
+
+    #[derive(Clone, Debug)]
+    struct CountedRule<'a> {
+        rule: Rule<'a>,
+        calls: Rc<Cell<usize>>,
+    }
+
+    impl<'a> Constraint<'a, Counted> for CountedRule<'a> {
+        fn is_satisfied(&self, env: &HashMap<sudoku::Cell, Number>) -> bool {
+            self.rule.is_satisfied(env)
+        }
+
+        fn prune(
+            &self,
+            domains: &mut Domains<sudoku::Cell, Number>,
+            assignments: &HashMap<sudoku::Cell, Number>,
+            variable: &sudoku::Cell,
+            value: &Number
+        ) -> usize {
+            self.calls.set(self.calls.get() + 1);
+            self.rule.prune(domains, assignments, variable, value)
+        }
+
+        fn scope(&self) -> Vec<sudoku::Cell> {
+            self.rule.scope()
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Hash)]
+    struct Unsolved;
+
+    impl<'a> Solution<'a, Counted> for Unsolved {
+        fn construct(_: &Counted, _: &HashMap<sudoku::Cell, Number>) -> Unsolved {
+            Unsolved
+        }
+    }
+
+    impl<'a> CSP<'a> for Counted {
+        type Value = Number;
+        type Variable = sudoku::Cell;
+        type Constraint = CountedRule<'a>;
+        type Constraints = Vec<CountedRule<'a>>;
+        type Solution = Unsolved;
+
+        fn constraints(&'a self) -> Vec<CountedRule<'a>> {
+            self.sudoku.constraints().into_iter()
+                .map(|rule| CountedRule { rule: rule, calls: self.calls.clone() })
+                .collect()
+        }
+
+        fn domains(&'a self) -> HashMap<sudoku::Cell, HashSet<Number>> {
+            self.sudoku.domains()
+        }
+    }
+
+    #[test]
+    fn test_constraint_graph() {
+        let counted = Counted {
+            sudoku: Sudoku::load(&PathBuf::from("data/variants/sudoku.csv"), &"empty".to_owned()).unwrap(),
+            calls: Rc::new(Cell::new(0)),
+        };
+        let graph = ConstraintGraph::<Counted>::new(counted.constraints());
+        assert_eq!(graph.constraints.len(), 810);
+
+        // a cell shares a row, a column or a box with 20 others, so only the
+        // constraints between them are pruned with
+        let mut domains = Domains::new(counted.domains());
+        let mut assignments = HashMap::new();
+        let variable = *domains.iter().map(|(variable, _)| variable).min().unwrap();
+        let value = *domains.get(&variable).unwrap().iter().min().unwrap();
+        domains.hide(&variable);
+        assignments.insert(variable, value);
+        assert!(graph.prune(&mut domains, &assignments, &variable, &value));
+        assert_eq!(counted.calls.get(), 20);
+        assert_eq!(graph.over(&variable).count(), 20);
+        assert!(graph.check(&variable, &assignments));
+    }
+
+    #[test]
+    fn test_maintaining_arc_consistency() {
+        let sudoku = Sudoku::load(&PathBuf::from("data/sudoku.csv"), &"14".to_owned()).unwrap();
+        assert_eq!(
+            collect(|f| maintaining_arc_consistency(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, f)),
+            collect(|f| forward_checking(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, f))
+        );
+
+        let jolka = Jolka::load(&PathBuf::from("data/jolka/puzzle1"), &PathBuf::from("data/jolka/words1")).unwrap();
+        let solutions = collect(|f| maintaining_arc_consistency(&jolka, VariableSelector::OrderOfDefinition, ValueSelector::OrderOfDefinition, Limit::All, f));
+        assert_eq!(solutions.len(), 2);
+        assert_eq!(solutions, collect(|f| backtracking(&jolka, VariableSelector::OrderOfDefinition, ValueSelector::OrderOfDefinition, Limit::All, f)));
+    }
+
+    #[test]
+    fn test_limit() {
+        let jolka = Jolka::load(&PathBuf::from("data/jolka/puzzle1"), &PathBuf::from("data/jolka/words1")).unwrap();
+        for limit in [1, 2, 3].iter() {
+            let expected = (*limit).min(2);
+            assert_eq!(collect(|f| backtracking(&jolka, VariableSelector::OrderOfDefinition, ValueSelector::OrderOfDefinition, Limit::First(*limit), f)).len(), expected);
+            assert_eq!(collect(|f| forward_checking(&jolka, VariableSelector::OrderOfDefinition, ValueSelector::OrderOfDefinition, Limit::First(*limit), f)).len(), expected);
+            assert_eq!(collect(|f| maintaining_arc_consistency(&jolka, VariableSelector::OrderOfDefinition, ValueSelector::OrderOfDefinition, Limit::First(*limit), f)).len(), expected);
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        let sudoku = Sudoku::load(&PathBuf::from("data/sudoku.csv"), &"14".to_owned()).unwrap();
+        let report = forward_checking(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, |_| {});
+        assert_eq!(report.to_csv().split(',').count(), SearchReport::csv_header().split(',').count());
+        assert!(report.to_json().contains(&format!("\"visited_nodes\":{},", report.visited_nodes)));
+        // depth 0 counts the values tried for the first variable, at most 9
+        assert!(report.nodes_per_depth[0] <= 9);
+        assert!(report.total_time >= report.first_solution_time.unwrap());
+    }
+
+    #[test]
+    fn test_variable_selectors() {
+        let jolka = Jolka::load(&PathBuf::from("data/jolka/puzzle1"), &PathBuf::from("data/jolka/words1")).unwrap();
+        let expected = collect(|f| forward_checking(&jolka, VariableSelector::OrderOfDefinition, ValueSelector::OrderOfDefinition, Limit::All, f));
+        for variable_selector in [
+            VariableSelector::Degree,
+            VariableSelector::MostConstrainedVariableDegree,
+            VariableSelector::DomainOverDegree,
+            VariableSelector::DomainOverWeightedDegree
+        ].iter() {
+            assert_eq!(collect(|f| forward_checking(&jolka, *variable_selector, ValueSelector::OrderOfDefinition, Limit::All, f)), expected);
+            assert_eq!(collect(|f| maintaining_arc_consistency(&jolka, *variable_selector, ValueSelector::OrderOfDefinition, Limit::All, f)), expected);
+        }
+
+        let sudoku = Sudoku::load(&PathBuf::from("data/sudoku.csv"), &"30".to_owned()).unwrap();
+        let mcv = forward_checking(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, |_| {});
+        let wdeg = forward_checking(&sudoku, VariableSelector::DomainOverWeightedDegree, ValueSelector::OrderOfDefinition, Limit::All, |_| {});
+        assert_eq!(wdeg.solutions_found, 1);
+        assert!(wdeg.visited_nodes < mcv.visited_nodes);
+    }
+
+    #[test]
+    fn test_all_different() {
+        let sudoku = Sudoku::load(&PathBuf::from("data/sudoku.csv"), &"8".to_owned()).unwrap();
+        let expected = collect(|f| forward_checking(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, f));
+        let fc = forward_checking(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, |_| {});
+
+        let sudoku = sudoku.all_different(true);
+        assert_eq!(collect(|f| forward_checking(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, f)), expected);
+        assert_eq!(collect(|f| maintaining_arc_consistency(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, f)), expected);
+        for learn_nogoods in [false, true].iter() {
+            assert_eq!(collect(|f| conflict_directed_backjumping(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, *learn_nogoods, f)), expected);
+        }
+        let all_different = forward_checking(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, |_| {});
+        assert!(all_different.visited_nodes < fc.visited_nodes);
+    }
+
+    #[test]
+    fn test_empty_domains() {
+        let jolka = Jolka::load(&PathBuf::from("data/jolka/puzzle1"), &PathBuf::from("data/jolka/words1")).unwrap();
+        let graph = ConstraintGraph::new(jolka.constraints());
+        let mut domains = Domains::new(jolka.domains().into_keys()
+            .map(|line| (line, HashSet::new()))
+            .collect());
+        let mut assignments = HashMap::new();
+        let variable_selectors = [
+            VariableSelector::OrderOfDefinition,
+            VariableSelector::MostConstrainedVariable,
+            VariableSelector::Random,
+            VariableSelector::Degree,
+            VariableSelector::MostConstrainedVariableDegree,
+            VariableSelector::DomainOverDegree,
+            VariableSelector::DomainOverWeightedDegree
+        ];
+        let value_selectors = [
+            ValueSelector::OrderOfDefinition,
+            ValueSelector::LeastConstrainingValue,
+            ValueSelector::Random,
+            ValueSelector::LeastOccuringValue
+        ];
+        for variable_selector in variable_selectors.iter() {
+            let variable = variable_selector.select::<Jolka>(&domains, &assignments, &graph).unwrap();
+            for value_selector in value_selectors.iter() {
+                assert!(value_selector.values::<Jolka>(&variable, &mut domains, &assignments, &graph).is_empty());
+            }
+        }
+
+        for line in jolka.domains().into_keys() {
+            assignments.insert(line, String::new());
+        }
+        for variable_selector in variable_selectors.iter() {
+            assert!(variable_selector.select::<Jolka>(&domains, &assignments, &graph).is_none());
+        }
+    }
+
+    #[test]
+    fn test_conflict_directed_backjumping() {
+        for puzzle in 1..=4 {
+            let jolka = Jolka::load(
+                &PathBuf::from(format!("data/jolka/puzzle{}", puzzle)),
+                &PathBuf::from(format!("data/jolka/words{}", puzzle))
+            ).unwrap();
+            let expected = collect(|f| forward_checking(&jolka, VariableSelector::OrderOfDefinition, ValueSelector::OrderOfDefinition, Limit::All, f));
+            for learn_nogoods in [false, true].iter() {
+                assert_eq!(collect(|f| conflict_directed_backjumping(&jolka, VariableSelector::OrderOfDefinition, ValueSelector::OrderOfDefinition, Limit::All, *learn_nogoods, f)), expected);
+            }
+        }
+
+        let sudoku = Sudoku::load(&PathBuf::from("data/sudoku.csv"), &"8".to_owned()).unwrap();
+        let expected = collect(|f| forward_checking(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, f));
+        let fc = forward_checking(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, |_| {});
+        for learn_nogoods in [false, true].iter() {
+            assert_eq!(collect(|f| conflict_directed_backjumping(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, *learn_nogoods, f)), expected);
+            let cbj = conflict_directed_backjumping(&sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, *learn_nogoods, |_| {});
+            assert!(cbj.visited_nodes < fc.visited_nodes);
+        }
+        let jolka = Jolka::load(&PathBuf::from("data/jolka/puzzle1"), &PathBuf::from("data/jolka/words1")).unwrap();
+        assert_eq!(collect(|f| conflict_directed_backjumping(&jolka, VariableSelector::OrderOfDefinition, ValueSelector::OrderOfDefinition, Limit::First(1), true, f)).len(), 1);
+    }
+
+    #[test]
+    fn test_nogood() {
+        use crate::csp::Constraint;
+
+        let jolka = Jolka::load(&PathBuf::from("data/jolka/puzzle1"), &PathBuf::from("data/jolka/words1")).unwrap();
+        let domains = jolka.domains();
+        let mut lines = domains.keys().cloned().collect::<Vec<_>>();
+        lines.sort();
+        let (a, b, c) = (lines[0], lines[1], lines[2]);
+        let word = |line| domains[&line].iter().next().unwrap().clone();
+        let (word_a, word_b, word_c) = (word(a), word(b), word(c));
+        let nogood = Nogood { assignments: vec![(a, word_a.clone()), (b, word_b.clone()), (c, word_c.clone())] };
+
+        let mut env = HashMap::new();
+        env.insert(a, word_a.clone());
+        env.insert(b, word_b.clone());
+        assert!(Constraint::<Jolka>::is_satisfied(&nogood, &env));
+        env.insert(c, word_c.clone());
+        assert!(!Constraint::<Jolka>::is_satisfied(&nogood, &env));
+
+        // with two variables of the nogood unassigned nothing is pruned, and
+        // once only one is left its value goes
+        let mut domains = Domains::new(domains);
+        assert_eq!(Constraint::<Jolka>::prune(&nogood, &mut domains, &HashMap::new(), &a, &word_a), 0);
+        let mut assignments = HashMap::new();
+        assignments.insert(a, word_a.clone());
+        assert_eq!(Constraint::<Jolka>::prune(&nogood, &mut domains, &assignments, &b, &word_b), 1);
+        assert!(!domains.get(&c).unwrap().contains(&word_c));
+        // a different value of the last assigned variable satisfies the nogood
+        let other_word_b = domains.get(&b).unwrap().iter().find(|other| **other != word_b).cloned();
+        if let Some(other_word_b) = other_word_b {
+            assert_eq!(Constraint::<Jolka>::prune(&nogood, &mut domains, &assignments, &b, &other_word_b), 0);
+        }
     }
-    return solutions;
 }
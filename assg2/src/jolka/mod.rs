@@ -43,7 +43,7 @@ impl fmt::Debug for Line {
     }
 }
 
-use crate::csp::Constraint;
+use crate::csp::{Constraint, Domains};
 
 #[derive(Clone)]
 pub struct Intersection<'a> {
@@ -62,54 +62,40 @@ impl Constraint<'_, Jolka> for Intersection<'_> {
                 let char_from_row_index = x - self.row.start_position.0;
                 let char_from_column_index = y - self.column.start_position.1;
                 row_word.chars().nth(char_from_row_index)
-                    .map_or(false, |char_from_row: char| -> bool {
-                        column_word.chars().nth(char_from_column_index)
-                            .map_or(false, |char_from_column: char| -> bool {
-                                char_from_row == char_from_column
-                            })
+                    .is_some_and(|char_from_row: char| -> bool {
+                        column_word.chars().nth(char_from_column_index) == Some(char_from_row)
                     })
             },
             _ => true
         }
     }
 
-    fn prune(&self, domains: &mut HashMap<Line, HashSet<String>>, variable: &Line, value: &String) -> usize {
+    fn prune(&self, domains: &mut Domains<Line, String>, _: &HashMap<Line, String>, variable: &Line, value: &String) -> usize {
         let (start_x, _) = self.row.start_position;
         let (_, start_y) = self.column.start_position;
         let (x, y) = self.intersection_position;
-        if variable == self.row {
-            let mut removed = 0;
-            let character = value.chars().nth(x - start_x).unwrap();
-            domains.get_mut(self.column).map(|domain| {
-                for word in domain.clone() {
-                    if let Some(other_character) = word.chars().nth(y - start_y) {
-                        if other_character != character {
-                            if domain.remove(&word) {
-                                removed += 1;
-                            }
-                        }
-                    }
-                }
-            });
-            return removed;
+        let (other, index, character) = if variable == self.row {
+            (self.column, y - start_y, value.chars().nth(x - start_x).unwrap())
         } else if variable == self.column {
-            let mut removed = 0;
-            let character = value.chars().nth(y - start_y).unwrap();
-            domains.get_mut(self.row).map(|domain| {
-                for word in domain.clone() {
-                    if let Some(other_character) = word.chars().nth(x - start_x) {
-                        if other_character != character {
-                            if domain.remove(&word) {
-                                removed += 1;
-                            }
-                        }
-                    }
-                }
-            });
-            return removed;
+            (self.row, x - start_x, value.chars().nth(y - start_y).unwrap())
         } else {
             return 0;
+        };
+        let mismatched: Vec<String> = match domains.get(other) {
+            Some(domain) => domain.iter()
+                .filter(|word| word.chars().nth(index).is_some_and(|other_character| other_character != character))
+                .cloned()
+                .collect(),
+            None => return 0
+        };
+        for word in &mismatched {
+            domains.remove(other, word);
         }
+        return mismatched.len();
+    }
+
+    fn scope(&self) -> Vec<Line> {
+        vec![*self.row, *self.column]
     }
 }
 
@@ -182,9 +168,9 @@ impl Jolka {
     pub fn load(board_path: &PathBuf, words_path: &PathBuf) -> Result<Jolka, parser::Error> {
         use log::info;
 
-        let board = parser::read_board(&board_path)?;
+        let board = parser::read_board(board_path)?;
         info!("Parsed the jolka board file");
-        let words = parser::read_words(&words_path)?;
+        let words = parser::read_words(words_path)?;
         info!("Parsed the jolka words file");
 
         let rows = parser::parse_lines(&board, Axis::Horizontal);
@@ -214,17 +200,17 @@ impl<'a> CSP<'a> for Jolka {
         }).collect();
         for row in &self.rows {
             let (x, y) = row.start_position;
-            for i in x..(x + row.line_length) {
-                rows_map[y][i] = Some(&row);
+            for cell in &mut rows_map[y][x..(x + row.line_length)] {
+                *cell = Some(row);
             }
         }
         for column in &self.columns {
             let (x, y) = column.start_position;
-            for i in y..(y + column.line_length) {
-                if let Some(row_on_the_same_cell) = rows_map[i][x] {
+            for (i, rows_map_row) in rows_map.iter().enumerate().skip(y).take(column.line_length) {
+                if let Some(row_on_the_same_cell) = rows_map_row[x] {
                     constraints.push(Intersection {
                         row: row_on_the_same_cell,
-                        column: &column,
+                        column: column,
                         intersection_position: (x, i),
                     });
                 }
@@ -247,18 +233,3 @@ impl<'a> CSP<'a> for Jolka {
         return domains;
     }
 }
-
-pub struct Words<'a> {
-    source: &'a Vec<String>,
-    current: usize,
-}
-
-impl Iterator for Words<'_> {
-    type Item = String;
-
-    fn next(&mut self) -> Option<String> {
-        let result = self.source.get(self.current).map(|word| word.clone());
-        self.current += 1;
-        return result;
-    }
-}
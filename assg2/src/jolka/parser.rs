@@ -41,7 +41,7 @@ pub fn read_board(path: &PathBuf) -> Result<Board> {
 pub fn read_words(path: &PathBuf) -> Result<Vec<String>> {
     use std::fs;
 
-    let contents = fs::read_to_string(path).context(LoadBoard)?;
+    let contents = fs::read_to_string(path).context(LoadWords)?;
     return Ok(parse_words(&contents));
 }
 
@@ -91,7 +91,7 @@ use super::Axis;
 pub fn parse_lines(lines: &Vec<Vec<Cell>>, axis: Axis) -> Vec<Line> {
     let mut rows = Vec::new();
     for (i, board_row) in lines.iter().enumerate() {
-        let columns_occupied = get_cells_occupied(&board_row);
+        let columns_occupied = get_cells_occupied(board_row);
         for line in columns_occupied {
             let row = Line {
                 start_position: match axis {
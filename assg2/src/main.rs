@@ -1,3 +1,10 @@
+#![allow(
+    clippy::needless_return,
+    clippy::redundant_field_names,
+    clippy::ptr_arg,
+    clippy::upper_case_acronyms,
+)]
+
 use structopt::StructOpt;
 
 mod cli;
@@ -5,52 +12,80 @@ mod csp;
 mod jolka;
 mod sudoku;
 
+/// Searches for the solutions of the problem with the algorithm, heuristics
+/// and limit of the configuration.
+fn solve<'a, P: csp::CSP<'a>, F: FnMut(P::Solution)>(problem: &'a P, config: &cli::Config, on_solution: F)
+    -> csp::solvers::SearchReport
+{
+    let (variable_selector, value_selector, limit) =
+        (config.variable_selection_heuristic, config.value_selection_heuristic, config.limit);
+    match config.algorithm {
+        cli::Algorithm::Backtracking =>
+            csp::solvers::backtracking(problem, variable_selector, value_selector, limit, on_solution),
+        cli::Algorithm::ForwardChecking =>
+            csp::solvers::forward_checking(problem, variable_selector, value_selector, limit, on_solution),
+        cli::Algorithm::MaintainingArcConsistency =>
+            csp::solvers::maintaining_arc_consistency(problem, variable_selector, value_selector, limit, on_solution),
+        cli::Algorithm::ConflictDirectedBackjumping => csp::solvers::conflict_directed_backjumping(
+            problem, variable_selector, value_selector, limit, false, on_solution),
+        cli::Algorithm::ConflictDirectedBackjumpingWithNogoods => csp::solvers::conflict_directed_backjumping(
+            problem, variable_selector, value_selector, limit, true, on_solution),
+    }
+}
+
 fn main() -> cli::Result {
     let config = cli::Config::from_args();
     cli::setup_env_logger(&config.verbosity)?;
 
-    use log::info;
+    fn print_solution<S: std::fmt::Debug>(solution: S) {
+        println!("{:?}\n", solution);
+    }
+
+    fn print_report(report: &csp::solvers::SearchReport, format: &Option<cli::ReportFormat>) {
+        match format {
+            Some(cli::ReportFormat::CSV) => println!("{}\n{}", csp::solvers::SearchReport::csv_header(), report.to_csv()),
+            Some(cli::ReportFormat::JSON) => println!("{}", report.to_json()),
+            None => {}
+        }
+    }
 
-    match config.problem {
+    match &config.problem {
         cli::Problem::Jolka { jolka_puzzle_path, jolka_words_path } => {
-            let jolka = jolka::Jolka::load(&jolka_puzzle_path, &jolka_words_path)?;
-
-            let solutions = match config.algorithm {
-                cli::Algorithm::Backtracking => csp::solvers::backtracking(
-                    &jolka,
-                    config.variable_selection_heuristic,
-                    config.value_selection_heuristic,
-                ),
-                cli::Algorithm::ForwardChecking => csp::solvers::forward_checking(
-                    &jolka,
-                    config.variable_selection_heuristic,
-                    config.value_selection_heuristic,
-                )
-            };
-            info!("Displaying the solutions");
-            for solution in solutions {
-                println!("{:?}\n", solution);
+            let jolka = jolka::Jolka::load(jolka_puzzle_path, jolka_words_path)?;
+
+            let report = solve(&jolka, &config, print_solution);
+            print_report(&report, &config.report_format);
+        },
+        cli::Problem::Sudoku {
+            sudoku_path, sudoku_id, all_different, diagonal, hyper, regions_path, cages_path
+        } => {
+            let mut sudoku = sudoku::Sudoku::load(sudoku_path, sudoku_id)?
+                .all_different(*all_different)
+                .diagonal(*diagonal)
+                .hyper(*hyper);
+            if let Some(regions_path) = regions_path {
+                sudoku = sudoku.jigsaw(regions_path)?;
+            }
+            if let Some(cages_path) = cages_path {
+                sudoku = sudoku.killer(cages_path)?;
             }
+
+            let report = solve(&sudoku, &config, print_solution);
+            print_report(&report, &config.report_format);
         },
-        cli::Problem::Sudoku { sudoku_path, sudoku_id } => {
-            let sudoku = sudoku::Sudoku::load(&sudoku_path, &sudoku_id)?;
-
-            let solutions = match config.algorithm {
-                cli::Algorithm::Backtracking => csp::solvers::backtracking(
-                    &sudoku,
-                    config.variable_selection_heuristic,
-                    config.value_selection_heuristic,
-                ),
-                cli::Algorithm::ForwardChecking => csp::solvers::forward_checking(
-                    &sudoku,
-                    config.variable_selection_heuristic,
-                    config.value_selection_heuristic,
-                )
-            };
-            info!("Displaying the solutions");
-            for solution in solutions {
-                println!("{:?}\n", solution);
+        cli::Problem::Bench { sudoku_path, all_different } => {
+            use log::info;
+            use std::time::Instant;
+
+            let start_time = Instant::now();
+            println!("id,{}", csp::solvers::SearchReport::csv_header());
+            for sudoku_id in sudoku::Sudoku::ids(sudoku_path)? {
+                let sudoku = sudoku::Sudoku::load(sudoku_path, &sudoku_id)?.all_different(*all_different);
+
+                let report = solve(&sudoku, &config, |_| {});
+                println!("{},{}", sudoku_id, report.to_csv());
             }
+            info!("Solved every board in {:.2?}", start_time.elapsed());
         }
     }
 
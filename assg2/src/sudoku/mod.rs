@@ -2,14 +2,14 @@ mod parser;
 
 use std::fmt;
 
+/// A number from 1 to the size of the board, printed as a letter past 9.
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Ord, PartialOrd)]
-pub enum Number {
-    One = 1, Two = 2, Three = 3, Four = 4, Five = 5, Six = 6, Seven = 7, Eight = 8, Nine = 9,
-}
+pub struct Number(u8);
 
 impl fmt::Debug for Number {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        (*self as u8).fmt(f)
+        let digit = std::char::from_digit(self.0 as u32, 36).unwrap();
+        write!(f, "{}", digit.to_ascii_uppercase())
     }
 }
 
@@ -24,7 +24,7 @@ impl fmt::Debug for Cell {
     }
 }
 
-use crate::csp::Constraint;
+use crate::csp::{AllDifferent, Constraint, Domains};
 
 #[derive(Eq, PartialEq, Hash, Clone)]
 pub struct Unique<'a> {
@@ -42,27 +42,19 @@ impl Constraint<'_, Sudoku> for Unique<'_> {
         }
     }
 
-    fn prune(&self, domains: &mut HashMap<Cell, HashSet<Number>>, variable: &Cell, value: &Number) -> usize {
+    fn prune(&self, domains: &mut Domains<Cell, Number>, _: &HashMap<Cell, Number>, variable: &Cell, value: &Number) -> usize {
         if variable == self.cell_a {
-            let mut removed = 0;
-            domains.get_mut(self.cell_b).map(|domain_b| {
-                if domain_b.remove(value) {
-                    removed += 1;
-                }
-            });
-            return removed;
+            return domains.remove(self.cell_b, value) as usize;
         } else if variable == self.cell_b {
-            let mut removed = 0;
-            domains.get_mut(self.cell_a).map(|domain_a| {
-                if domain_a.remove(value) {
-                    removed += 1;
-                };
-            });
-            return removed;
+            return domains.remove(self.cell_a, value) as usize;
         } else {
             return 0;
         }
     }
+
+    fn scope(&self) -> Vec<Cell> {
+        vec![*self.cell_a, *self.cell_b]
+    }
 }
 
 impl fmt::Debug for Unique<'_> {
@@ -71,44 +63,182 @@ impl fmt::Debug for Unique<'_> {
     }
 }
 
+/// A cage of a killer sudoku, whose cells hold different numbers adding up
+/// to the sum.
+#[derive(Clone)]
+pub struct Cage {
+    cells: Vec<Cell>,
+    sum: usize,
+    max: usize,
+}
+
+impl Constraint<'_, Sudoku> for Cage {
+    fn is_satisfied(&self, env: &HashMap<Cell, Number>) -> bool {
+        let numbers: Vec<usize> = self.cells.iter()
+            .filter_map(|cell| env.get(cell))
+            .map(|number| number.0 as usize)
+            .collect();
+        let mut seen = HashSet::new();
+        if !numbers.iter().all(|number| seen.insert(number)) {
+            return false;
+        }
+        // the cells left need at least 1 and at most the largest number each
+        let total: usize = numbers.iter().sum();
+        let left = self.cells.len() - numbers.len();
+        return total + left <= self.sum && total + left * self.max >= self.sum;
+    }
+
+    fn prune(&self, domains: &mut Domains<Cell, Number>, assignments: &HashMap<Cell, Number>, variable: &Cell, value: &Number) -> usize {
+        if !self.cells.contains(variable) {
+            return 0;
+        }
+        let mut assigned: HashMap<Cell, Number> = self.cells.iter()
+            .filter_map(|cell| assignments.get(cell).map(|number| (*cell, *number)))
+            .collect();
+        assigned.insert(*variable, *value);
+        let left = self.sum as isize - assigned.values().map(|number| number.0 as isize).sum::<isize>();
+        let open: Vec<Cell> = self.cells.iter()
+            .filter(|cell| !assigned.contains_key(cell) && domains.contains(cell))
+            .cloned()
+            .collect();
+        let bounds: Vec<(isize, isize)> = open.iter()
+            .map(|cell| {
+                let domain = domains.get(cell).unwrap();
+                let min = domain.iter().min().map_or(0, |number| number.0 as isize);
+                let max = domain.iter().max().map_or(0, |number| number.0 as isize);
+                (min, max)
+            })
+            .collect();
+        let low: isize = bounds.iter().map(|(min, _)| min).sum();
+        let high: isize = bounds.iter().map(|(_, max)| max).sum();
+
+        let mut removed = 0;
+        for (cell, (min, max)) in open.iter().zip(bounds) {
+            // the least and the most the other open cells can add up to
+            let (others_low, others_high) = (low - min, high - max);
+            let unfit: Vec<Number> = domains.get(cell).unwrap().iter()
+                .filter(|number| {
+                    let n = number.0 as isize;
+                    assigned.values().any(|other| other == *number) || n + others_low > left || n + others_high < left
+                })
+                .cloned()
+                .collect();
+            for number in unfit {
+                if domains.remove(cell, &number) {
+                    removed += 1;
+                }
+            }
+        }
+        return removed;
+    }
+
+    fn scope(&self) -> Vec<Cell> {
+        self.cells.clone()
+    }
+}
+
+impl fmt::Debug for Cage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sum{:?} = {}", self.cells, self.sum)
+    }
+}
+
+/// A constraint of a sudoku, either between a pair of cells, over a whole
+/// row, column, box or any other group of the variants, or over a cage.
+#[derive(Clone)]
+pub enum Rule<'a> {
+    Unique(Unique<'a>),
+    AllDifferent(AllDifferent<Cell>),
+    Cage(Cage),
+}
+
+impl Constraint<'_, Sudoku> for Rule<'_> {
+    fn is_satisfied(&self, env: &HashMap<Cell, Number>) -> bool {
+        match self {
+            Rule::Unique(unique) => unique.is_satisfied(env),
+            Rule::AllDifferent(all_different) => Constraint::<Sudoku>::is_satisfied(all_different, env),
+            Rule::Cage(cage) => cage.is_satisfied(env)
+        }
+    }
+
+    fn prune(&self, domains: &mut Domains<Cell, Number>, assignments: &HashMap<Cell, Number>, variable: &Cell, value: &Number) -> usize {
+        match self {
+            Rule::Unique(unique) => unique.prune(domains, assignments, variable, value),
+            Rule::AllDifferent(all_different) => Constraint::<Sudoku>::prune(all_different, domains, assignments, variable, value),
+            Rule::Cage(cage) => cage.prune(domains, assignments, variable, value)
+        }
+    }
+
+    fn scope(&self) -> Vec<Cell> {
+        match self {
+            Rule::Unique(unique) => unique.scope(),
+            Rule::AllDifferent(all_different) => Constraint::<Sudoku>::scope(all_different),
+            Rule::Cage(cage) => cage.scope()
+        }
+    }
+
+    fn revise(&self, domains: &mut Domains<Cell, Number>, variables: &[Cell]) -> Vec<Cell> {
+        match self {
+            Rule::Unique(unique) => unique.revise(domains, variables),
+            Rule::AllDifferent(all_different) => Constraint::<Sudoku>::revise(all_different, domains, variables),
+            Rule::Cage(cage) => cage.revise(domains, variables)
+        }
+    }
+}
+
+impl fmt::Debug for Rule<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Unique(unique) => unique.fmt(f),
+            Rule::AllDifferent(all_different) => all_different.fmt(f),
+            Rule::Cage(cage) => cage.fmt(f)
+        }
+    }
+}
+
 use crate::csp::Solution;
 
 #[derive(Eq, PartialEq, Hash, Clone)]
 pub struct SudokuSolution {
-    board: [[Number; 9]; 9],
+    board: Vec<Vec<Number>>,
+    box_size: usize,
 }
 
 impl<'a> Solution<'a, Sudoku> for SudokuSolution {
-    fn construct(_: &Sudoku, assignments: &HashMap<Cell, Number>) -> SudokuSolution {
-        let mut board: [[Number; 9]; 9] = [[Number::One; 9]; 9];
-        for y in 0..9 {
-            for x in 0..9 {
-                board[y][x] = *assignments.get(&Cell {
+    fn construct(sudoku: &Sudoku, assignments: &HashMap<Cell, Number>) -> SudokuSolution {
+        let size = sudoku.board.len();
+        let board = (0..size)
+            .map(|y| (0..size)
+                .map(|x| *assignments.get(&Cell {
                     position: (y, x),
-                }).unwrap();
-            }
-        }
+                }).unwrap())
+                .collect())
+            .collect();
         SudokuSolution {
             board: board,
+            box_size: sudoku.box_size,
         }
     }
 }
 
 impl fmt::Debug for SudokuSolution {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = self.board.len();
+        let separator = vec!["-".repeat(self.box_size * 2 + 1); self.box_size].join("+");
         let mut result = String::new();
-        for y in 0..9 {
-            for x in 0..9 {
+        for y in 0..size {
+            for x in 0..size {
                 result.push_str(format!(" {:?}", self.board[y][x]).as_str());
-                if x == 2 || x == 5 {
+                if x % self.box_size == self.box_size - 1 && x != size - 1 {
                     result.push_str(" |");
                 }
             }
-            if y != 8 {
+            if y != size - 1 {
                 result.push_str(" \n")
             }
-            if y == 2 || y == 5 {
-                result.push_str("-------+-------+-------\n")
+            if y % self.box_size == self.box_size - 1 && y != size - 1 {
+                result.push_str(&separator);
+                result.push('\n');
             }
         }
         write!(f, "{}", result)
@@ -118,9 +248,17 @@ impl fmt::Debug for SudokuSolution {
 use crate::csp::CSP;
 use std::collections::HashSet;
 
+/// An N²×N² sudoku, with the boxes or the jigsaw regions, and the additional
+/// groups and cages of its variants.
 pub struct Sudoku {
-    board: [[Cell; 9]; 9],
-    initial_board: [[Option<Number>; 9]; 9],
+    box_size: usize,
+    board: Vec<Vec<Cell>>,
+    initial_board: Vec<Vec<Option<Number>>>,
+    all_different: bool,
+    regions: Option<Vec<parser::Region>>,
+    diagonal: bool,
+    hyper: bool,
+    cages: Vec<(parser::Region, usize)>,
 }
 
 use std::path::PathBuf;
@@ -129,50 +267,119 @@ impl Sudoku {
     pub fn load(path: &PathBuf, board_id: &String) -> Result<Sudoku, parser::Error> {
         use log::info;
 
-        let initial_board = parser::read_board(&path, &board_id)?;
+        let initial_board = parser::read_board(path, board_id)?;
         info!("Parsed the sudoku board file");
 
+        let size = initial_board.len();
         Ok(Sudoku {
-            board: parser::make_rows_of_cells(),
+            box_size: (1..).find(|n| n * n >= size).unwrap(),
+            board: parser::make_rows_of_cells(size),
             initial_board: initial_board,
+            all_different: false,
+            regions: None,
+            diagonal: false,
+            hyper: false,
+            cages: Vec::new(),
         })
     }
+
+    /// The ids of every board in the file.
+    pub fn ids(path: &PathBuf) -> Result<Vec<String>, parser::Error> {
+        parser::read_board_ids(path)
+    }
+
+    /// Constrains every row, column and box with a single `AllDifferent`
+    /// instead of a `Unique` for every pair of its cells.
+    pub fn all_different(mut self, all_different: bool) -> Sudoku {
+        self.all_different = all_different;
+        return self;
+    }
+
+    /// Also requires different numbers on both main diagonals, as in an
+    /// X-sudoku.
+    pub fn diagonal(mut self, diagonal: bool) -> Sudoku {
+        self.diagonal = diagonal;
+        return self;
+    }
+
+    /// Also requires different numbers in the boxes of a hyper-sudoku, which
+    /// lie between the regular ones.
+    pub fn hyper(mut self, hyper: bool) -> Sudoku {
+        self.hyper = hyper;
+        return self;
+    }
+
+    /// Replaces the boxes with the jigsaw regions from the file.
+    pub fn jigsaw(mut self, path: &PathBuf) -> Result<Sudoku, parser::Error> {
+        self.regions = Some(parser::read_regions(path, self.board.len())?);
+        return Ok(self);
+    }
+
+    /// Adds the killer cages from the file.
+    pub fn killer(mut self, path: &PathBuf) -> Result<Sudoku, parser::Error> {
+        self.cages = parser::read_cages(path, self.board.len())?;
+        return Ok(self);
+    }
 }
 
 impl<'a> CSP<'a> for Sudoku {
     type Value = Number;
     type Variable = Cell;
-    type Constraint = Unique<'a>;
-    type Constraints = HashSet<Unique<'a>>;
+    type Constraint = Rule<'a>;
+    type Constraints = Vec<Rule<'a>>;
     type Solution = SudokuSolution;
 
     fn constraints(&'a self) -> Self::Constraints {
         use itertools::Itertools;
 
-        let mut constraints = HashSet::new();
-        for group in parser::group_board_by_rows(&self.board).iter()
-            .chain(parser::group_board_by_columns(&self.board).iter())
-            .chain(parser::group_board_by_boxes(&self.board).iter())
-        {
-            for combination in group.iter().combinations(2) {
-                constraints.insert(Unique {
-                    cell_a: combination[0],
-                    cell_b: combination[1],
-                });
+        let mut groups = parser::group_board_by_rows(&self.board);
+        groups.extend(parser::group_board_by_columns(&self.board));
+        match &self.regions {
+            Some(regions) => groups.extend(parser::group_board_by_regions(&self.board, regions)),
+            None => groups.extend(parser::group_board_by_boxes(&self.board, self.box_size))
+        }
+        if self.diagonal {
+            groups.extend(parser::group_board_by_diagonals(&self.board));
+        }
+        if self.hyper {
+            groups.extend(parser::group_board_by_windows(&self.board, self.box_size));
+        }
+
+        let mut constraints: Vec<Rule<'a>> = if self.all_different {
+            groups.iter()
+                .map(|group| Rule::AllDifferent(AllDifferent::new(group.iter().map(|cell| **cell).collect())))
+                .collect()
+        } else {
+            // the cells sharing more than one group are constrained once
+            let mut unique = HashSet::new();
+            for group in &groups {
+                for combination in group.iter().cloned().combinations(2) {
+                    unique.insert(Unique {
+                        cell_a: combination[0],
+                        cell_b: combination[1],
+                    });
+                }
             }
+            unique.into_iter().map(Rule::Unique).collect()
+        };
+        for (cells, sum) in &self.cages {
+            constraints.push(Rule::Cage(Cage {
+                cells: cells.iter().map(|(x, y)| self.board[*x][*y]).collect(),
+                sum: *sum,
+                max: self.board.len(),
+            }));
         }
         return constraints;
     }
 
     fn domains(&'a self) -> HashMap<Cell, HashSet<Number>> {
+        let size = self.board.len();
         let mut domains = HashMap::new();
-        for y in 0..9 {
-            for x in 0..9 {
-                domains.insert(self.board[y][x], match self.initial_board[y][x] {
-                    Some(n) => [n].iter().cloned().collect(),
-                    None => Numbers {
-                        current: 0
-                    }.collect()
+        for (cells, numbers) in self.board.iter().zip(self.initial_board.iter()) {
+            for (cell, number) in cells.iter().zip(numbers.iter()) {
+                domains.insert(*cell, match number {
+                    Some(n) => [*n].iter().cloned().collect(),
+                    None => (1..=size).map(|n| Number(n as u8)).collect()
                 });
             }
         }
@@ -180,26 +387,66 @@ impl<'a> CSP<'a> for Sudoku {
     }
 }
 
-pub struct Numbers {
-    current: u8
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csp::solvers::{self, Limit, ValueSelector, VariableSelector};
+
+    fn solve(sudoku: &Sudoku, limit: Limit) -> HashSet<SudokuSolution> {
+        let mut solutions = HashSet::new();
+        solvers::forward_checking(sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, limit, |solution| {
+            solutions.insert(solution);
+        });
+        let constraints = sudoku.constraints();
+        for solution in &solutions {
+            let env: HashMap<Cell, Number> = solution.board.iter().enumerate()
+                .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, number)| (Cell { position: (y, x) }, *number)))
+                .collect();
+            assert!(constraints.iter().all(|constraint| constraint.is_satisfied(&env)));
+        }
+        return solutions;
+    }
+
+    fn solve_mac(sudoku: &Sudoku) -> HashSet<SudokuSolution> {
+        let mut solutions = HashSet::new();
+        solvers::maintaining_arc_consistency(sudoku, VariableSelector::MostConstrainedVariable, ValueSelector::OrderOfDefinition, Limit::All, |solution| {
+            solutions.insert(solution);
+        });
+        return solutions;
+    }
+
+    fn load(path: &str, board_id: &str) -> Sudoku {
+        Sudoku::load(&PathBuf::from(path), &board_id.to_owned()).unwrap()
+    }
 
-impl Iterator for Numbers {
-    type Item = Number;
+    #[test]
+    fn test_sizes() {
+        let sudoku = load("data/variants/sudoku.csv", "4x4");
+        assert_eq!(sudoku.domains().len(), 16);
+        let solutions = solve(&sudoku, Limit::All);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(format!("{:?}", solutions.iter().next().unwrap()), " 1 2 | 3 4 \n 3 4 | 1 2 \n-----+-----\n 2 3 | 4 1 \n 4 1 | 2 3");
 
-    fn next(&mut self) -> Option<Number> {
-        self.current += 1;
-        match self.current {
-            1 => Some(Number::One),
-            2 => Some(Number::Two),
-            3 => Some(Number::Three),
-            4 => Some(Number::Four),
-            5 => Some(Number::Five),
-            6 => Some(Number::Six),
-            7 => Some(Number::Seven),
-            8 => Some(Number::Eight),
-            9 => Some(Number::Nine),
-            _ => None
+        for board_id in ["16x16", "25x25"].iter() {
+            let sudoku = load("data/variants/sudoku.csv", board_id).all_different(true);
+            assert_eq!(solve(&sudoku, Limit::First(1)).len(), 1);
         }
     }
+
+    #[test]
+    fn test_variants() {
+        let sudoku = load("data/variants/sudoku.csv", "empty").all_different(true).diagonal(true).hyper(true);
+        assert_eq!(sudoku.constraints().len(), 27 + 2 + 4);
+        assert_eq!(solve(&sudoku, Limit::First(1)).len(), 1);
+
+        // the regions keep the solution of the board
+        let jigsaw = load("data/sudoku.csv", "1").jigsaw(&PathBuf::from("data/variants/regions")).unwrap();
+        assert_eq!(solve(&jigsaw, Limit::All), solve(&load("data/sudoku.csv", "1"), Limit::All));
+
+        let killer = load("data/variants/sudoku.csv", "empty").killer(&PathBuf::from("data/variants/cages")).unwrap();
+        let solutions = solve(&killer, Limit::All);
+        assert_eq!(solutions.len(), 16);
+        assert!(solutions.is_superset(&solve(&load("data/sudoku.csv", "1"), Limit::All)));
+        assert_eq!(solutions, solve_mac(&killer.all_different(true)));
+    }
 }
@@ -11,14 +11,25 @@ pub enum Error {
     InvalidBoardId { board_id: String },
     #[snafu(display("Invalid character {:?} at position {}", character, position))]
     InvalidCharacter { character: char, position: usize },
-    #[snafu(display("Not enough fields provided"))]
-    NotEnoughFields,
-    #[snafu(display("Too many fields provided"))]
-    TooManyFields,
+    #[snafu(display("{} fields do not make an N²×N² board", fields))]
+    InvalidSize { fields: usize },
+    #[snafu(display("{}×{} boards are too large to write the numbers as single characters", size, size))]
+    TooLarge { size: usize },
+    #[snafu(display("Could not load the regions file: {}", source))]
+    LoadRegions { source: std::io::Error },
+    #[snafu(display("The regions do not split the board into {} regions of {} cells", size, size))]
+    InvalidRegions { size: usize },
+    #[snafu(display("Could not load the cages file: {}", source))]
+    LoadCages { source: std::io::Error },
+    #[snafu(display("Invalid cage on line {}", line))]
+    InvalidCage { line: usize },
 }
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// The positions of the cells of a jigsaw region or a killer cage.
+pub type Region = Vec<(usize, usize)>;
+
 fn get_board_string(path: &PathBuf, board_id: &String) -> Result<String> {
     let mut reader = csv::ReaderBuilder::new()
         .has_headers(true)
@@ -35,79 +46,175 @@ fn get_board_string(path: &PathBuf, board_id: &String) -> Result<String> {
     return Err(Error::InvalidBoardId { board_id: board_id.clone() });
 }
 
-fn parse_board(source: &str) -> Result<[[Option<Number>; 9]; 9]> {
+pub fn read_board_ids(path: &PathBuf) -> Result<Vec<String>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .delimiter(b';')
+        .flexible(true)
+        .from_path(path)
+        .context(LoadBoard)?;
+    let mut ids = Vec::new();
+    for result in reader.records() {
+        let record = result.context(LoadBoard)?;
+        ids.push(record[0].to_owned());
+    }
+    return Ok(ids);
+}
+
+/// Parses a board of N²×N² fields, where the numbers past 9 are written as
+/// letters, e.g. `G` for 16.
+fn parse_board(source: &str) -> Result<Vec<Vec<Option<Number>>>> {
+    let fields = source.chars().count();
+    let box_size = (1..).find(|n: &usize| n.pow(4) >= fields).unwrap();
+    ensure!(box_size.pow(4) == fields, InvalidSize { fields: fields });
+    let size = box_size * box_size;
+    // the numbers are written in base 36, with no digit for 36 or more
+    ensure!(size < 36, TooLarge { size: size });
     let numbers = source.chars().enumerate().map(|(i, c)| {
-        match c {
-            '1' => Ok(Some(Number::One)),
-            '2' => Ok(Some(Number::Two)),
-            '3' => Ok(Some(Number::Three)),
-            '4' => Ok(Some(Number::Four)),
-            '5' => Ok(Some(Number::Five)),
-            '6' => Ok(Some(Number::Six)),
-            '7' => Ok(Some(Number::Seven)),
-            '8' => Ok(Some(Number::Eight)),
-            '9' => Ok(Some(Number::Nine)),
-            '.' => Ok(None),
+        match (c, c.to_digit(36)) {
+            ('.', _) => Ok(None),
+            (_, Some(n)) if n >= 1 && n as usize <= size => Ok(Some(Number(n as u8))),
              _  => Err(Error::InvalidCharacter { character: c, position: i })
         }
     }).collect::<Result<Vec<Option<Number>>, Error>>()?;
-    let mut iter = numbers.iter();
-    let mut board = [[None; 9]; 9];
-    for i in 0..81 {
-        match iter.next() {
-            Some(v) => board[i % 9][i / 9] = *v,
-            None => return Err(Error::NotEnoughFields)
-        }
+    let mut board = vec![vec![None; size]; size];
+    for (i, number) in numbers.into_iter().enumerate() {
+        board[i % size][i / size] = number;
     }
-    ensure!(iter.next().is_none(), TooManyFields);
     return Ok(board);
 }
 
-pub fn read_board(path: &PathBuf, board_id: &String) -> Result<[[Option<Number>; 9]; 9]> {
-    let board_string = get_board_string(path, &board_id)?;
+pub fn read_board(path: &PathBuf, board_id: &String) -> Result<Vec<Vec<Option<Number>>>> {
+    let board_string = get_board_string(path, board_id)?;
     return parse_board(&board_string);
 }
 
-use super::Cell;
+/// Parses the regions of a jigsaw sudoku, a line of characters for every row
+/// of the board, where the cells of a region share the same character.
+fn parse_regions(source: &str, size: usize) -> Result<Vec<Region>> {
+    use std::collections::BTreeMap;
 
-pub fn make_rows_of_cells() -> [[Cell; 9]; 9] {
-    let mut rows: [[Cell; 9]; 9] = [[Cell::default(); 9]; 9];
-    for y in 0..9 {
-        for x in 0..9 {
-            rows[y][x].position = (x, y)
+    let lines: Vec<Vec<char>> = source.split_whitespace().map(|line| line.chars().collect()).collect();
+    ensure!(lines.len() == size && lines.iter().all(|line| line.len() == size), InvalidRegions { size: size });
+    let mut regions: BTreeMap<char, Region> = BTreeMap::new();
+    for (y, line) in lines.iter().enumerate() {
+        for (x, label) in line.iter().enumerate() {
+            regions.entry(*label).or_default().push((x, y));
         }
     }
-    return rows;
+    ensure!(
+        regions.len() == size && regions.values().all(|region| region.len() == size),
+        InvalidRegions { size: size }
+    );
+    return Ok(regions.into_values().collect());
+}
+
+pub fn read_regions(path: &PathBuf, size: usize) -> Result<Vec<Region>> {
+    use std::fs;
+
+    let contents = fs::read_to_string(path).context(LoadRegions)?;
+    return parse_regions(&contents, size);
 }
 
-pub fn group_board_by_rows<T>(board: &[[T; 9]; 9]) -> [[&T; 9]; 9] {
-    let mut rows: [[&T; 9]; 9] = [[&board[0][0]; 9]; 9];
-    for y in 0..9 {
-        for x in 0..9 {
-            rows[y][x] = &board[y][x]
+/// Parses the cages of a killer sudoku, a line of characters for every row of
+/// the board like the regions, with dots outside of the cages, followed by a
+/// line with the character and the sum of every cage.
+fn parse_cages(source: &str, size: usize) -> Result<Vec<(Region, usize)>> {
+    use std::collections::HashMap;
+
+    let lines: Vec<&str> = source.lines().filter(|line| !line.trim().is_empty()).collect();
+    ensure!(lines.len() >= size, InvalidCage { line: lines.len() });
+    let mut cells: HashMap<char, Region> = HashMap::new();
+    for (y, line) in lines[..size].iter().enumerate() {
+        let labels: Vec<char> = line.trim().chars().collect();
+        ensure!(labels.len() == size, InvalidCage { line: y });
+        for (x, label) in labels.into_iter().enumerate() {
+            if label != '.' {
+                cells.entry(label).or_default().push((x, y));
+            }
+        }
+    }
+    let mut cages = Vec::new();
+    for (i, line) in lines[size..].iter().enumerate() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let cage = match fields.as_slice() {
+            [label, sum] if label.chars().count() == 1 => label.chars().next()
+                .and_then(|label| cells.remove(&label))
+                .zip(sum.parse::<usize>().ok()),
+            _ => None
+        };
+        match cage {
+            Some(cage) => cages.push(cage),
+            None => return Err(Error::InvalidCage { line: size + i })
         }
     }
-    return rows;
+    ensure!(cells.is_empty(), InvalidCage { line: lines.len() });
+    return Ok(cages);
+}
+
+pub fn read_cages(path: &PathBuf, size: usize) -> Result<Vec<(Region, usize)>> {
+    use std::fs;
+
+    let contents = fs::read_to_string(path).context(LoadCages)?;
+    return parse_cages(&contents, size);
+}
+
+use super::Cell;
+
+pub fn make_rows_of_cells(size: usize) -> Vec<Vec<Cell>> {
+    return (0..size)
+        .map(|y| (0..size).map(|x| Cell { position: (x, y) }).collect())
+        .collect();
+}
+
+pub fn group_board_by_rows<T>(board: &[Vec<T>]) -> Vec<Vec<&T>> {
+    return board.iter().map(|row| row.iter().collect()).collect();
+}
+
+pub fn group_board_by_columns<T>(board: &[Vec<T>]) -> Vec<Vec<&T>> {
+    return (0..board.len())
+        .map(|x| board.iter().map(|row| &row[x]).collect())
+        .collect();
 }
 
-pub fn group_board_by_columns<T>(board: &[[T; 9]; 9]) -> [[&T; 9]; 9] {
-    let mut columns: [[&T; 9]; 9] = [[&board[0][0]; 9]; 9];
-    for y in 0..9 {
-        for x in 0..9 {
-            columns[y][x] = &board[x][y]
+pub fn group_board_by_boxes<T>(board: &[Vec<T>], box_size: usize) -> Vec<Vec<&T>> {
+    let mut boxes: Vec<Vec<&T>> = vec![Vec::new(); board.len()];
+    for (y, row) in board.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            boxes[(y / box_size) * box_size + x / box_size].push(cell);
         }
     }
-    return columns;
+    return boxes;
 }
 
-pub fn group_board_by_boxes<T>(board: &[[T; 9]; 9]) -> [[&T; 9]; 9] {
-    let mut boxes: [[&T; 9]; 9] = [[&board[0][0]; 9]; 9];
-    for y in 0..9 {
-        for x in 0..9 {
-            boxes[(y / 3) * 3 + x / 3][(y % 3) * 3 + x % 3] = &board[y][x]
+/// The two main diagonals of an X-sudoku.
+pub fn group_board_by_diagonals<T>(board: &[Vec<T>]) -> Vec<Vec<&T>> {
+    let size = board.len();
+    return vec![
+        (0..size).map(|i| &board[i][i]).collect(),
+        (0..size).map(|i| &board[i][size - 1 - i]).collect()
+    ];
+}
+
+/// The boxes of a hyper-sudoku, one cell apart from each other and from the
+/// edges of the board.
+pub fn group_board_by_windows<T>(board: &[Vec<T>], box_size: usize) -> Vec<Vec<&T>> {
+    let offsets: Vec<usize> = (0..box_size - 1).map(|i| 1 + i * (box_size + 1)).collect();
+    let mut windows = Vec::new();
+    for y in &offsets {
+        for x in &offsets {
+            windows.push((0..box_size * box_size)
+                .map(|i| &board[y + i / box_size][x + i % box_size])
+                .collect());
         }
     }
-    return boxes;
+    return windows;
+}
+
+pub fn group_board_by_regions<'b, T>(board: &'b [Vec<T>], regions: &[Region]) -> Vec<Vec<&'b T>> {
+    return regions.iter()
+        .map(|region| region.iter().map(|(x, y)| &board[*x][*y]).collect())
+        .collect();
 }
 
 #[cfg(test)]
@@ -118,16 +225,16 @@ mod tests {
     fn test_parse_board() {
         assert_eq!(
             parse_board("....3..5..8...5..1.592.63..42.5......6.4.2........9..5.46.....3.....78..........4").unwrap(),
-            [
-                [None, None, None, Some(Number::Four), None, None, None, None, None],
-                [None, Some(Number::Eight), Some(Number::Five), Some(Number::Two), Some(Number::Six), None, Some(Number::Four), None, None],
-                [None, None, Some(Number::Nine), None, None, None, Some(Number::Six), None, None],
-                [None, None, Some(Number::Two), Some(Number::Five), Some(Number::Four), None, None, None, None],
-                [Some(Number::Three), None, None, None, None, None, None, None, None],
-                [None, Some(Number::Five), Some(Number::Six), None, Some(Number::Two), Some(Number::Nine), None, Some(Number::Seven), None],
-                [None, None, Some(Number::Three), None, None, None, None, Some(Number::Eight), None],
-                [Some(Number::Five), None, None, None, None, None, None, None, None],
-                [None, Some(Number::One), None, None, None, Some(Number::Five), Some(Number::Three), None, Some(Number::Four)]
+            vec![
+                vec![None, None, None, Some(Number(4)), None, None, None, None, None],
+                vec![None, Some(Number(8)), Some(Number(5)), Some(Number(2)), Some(Number(6)), None, Some(Number(4)), None, None],
+                vec![None, None, Some(Number(9)), None, None, None, Some(Number(6)), None, None],
+                vec![None, None, Some(Number(2)), Some(Number(5)), Some(Number(4)), None, None, None, None],
+                vec![Some(Number(3)), None, None, None, None, None, None, None, None],
+                vec![None, Some(Number(5)), Some(Number(6)), None, Some(Number(2)), Some(Number(9)), None, Some(Number(7)), None],
+                vec![None, None, Some(Number(3)), None, None, None, None, Some(Number(8)), None],
+                vec![Some(Number(5)), None, None, None, None, None, None, None, None],
+                vec![None, Some(Number(1)), None, None, None, Some(Number(5)), Some(Number(3)), None, Some(Number(4))]
             ]
         );
     }
@@ -151,16 +258,18 @@ mod tests {
         ).is_err());
     }
 
+    #[test]
+    fn test_parse_board_error_too_large() {
+        assert!(parse_board(&".".repeat(36 * 36)).is_err());
+    }
+
     #[test]
     fn test_group_board() {
-        let mut board = [[0; 9]; 9];
-        for y in 0..9 {
-            for x in 0..9 {
-                board[y][x] = y * 9 + x
-            }
-        }
+        let board: Vec<Vec<usize>> = (0..9)
+            .map(|y| (0..9).map(|x| y * 9 + x).collect())
+            .collect();
         let columns = group_board_by_columns(&board);
-        let boxes = group_board_by_boxes(&board);
+        let boxes = group_board_by_boxes(&board, 3);
 
         assert_eq!(
             columns,
@@ -188,4 +297,57 @@ mod tests {
              [&60, &61, &62, &69, &70, &71, &78, &79, &80]]
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_group_board_variants() {
+        let board: Vec<Vec<usize>> = (0..4)
+            .map(|y| (0..4).map(|x| y * 4 + x).collect())
+            .collect();
+
+        assert_eq!(
+            group_board_by_diagonals(&board),
+            [[&0, &5, &10, &15],
+             [&3, &6, &9,  &12]]
+        );
+        assert_eq!(group_board_by_windows(&board, 2), [[&5, &6, &9, &10]]);
+        assert_eq!(
+            group_board_by_regions(&board, &[vec![(0, 0), (0, 1), (1, 0), (2, 0)]]),
+            [[&0, &1, &4, &8]]
+        );
+        assert_eq!(group_board_by_windows(&vec![vec![0; 9]; 9], 3).len(), 4);
+    }
+
+    #[test]
+    fn test_parse_board_sizes() {
+        let board = parse_board("1234341223414123").unwrap();
+        assert_eq!(board.len(), 4);
+        assert_eq!(board[1][0], Some(Number(2)));
+
+        let board = parse_board(&format!("G{}", ".".repeat(255))).unwrap();
+        assert_eq!(board.len(), 16);
+        assert_eq!(board[0][0], Some(Number(16)));
+
+        assert!(parse_board(&format!("5{}", ".".repeat(15))).is_err());
+    }
+
+    #[test]
+    fn test_parse_regions() {
+        let regions = parse_regions("aabb\naabb\nccdd\ncddd\n", 4);
+        assert!(regions.is_err());
+
+        let regions = parse_regions("aabb\nacbb\naccd\ncddd\n", 4).unwrap();
+        assert_eq!(regions[0], vec![(0, 0), (1, 0), (0, 1), (0, 2)]);
+        assert_eq!(regions.len(), 4);
+    }
+
+    #[test]
+    fn test_parse_cages() {
+        let cages = parse_cages("aab.\ncdb.\ncd..\n....\n\na 3\nb 7\nc 5\nd 4\n", 4).unwrap();
+        assert_eq!(cages.len(), 4);
+        assert!(cages.contains(&(vec![(2, 0), (2, 1)], 7)));
+
+        // a cage without a sum
+        assert!(parse_cages("aab.\ncdb.\ncd..\n....\n\na 3\nb 7\nc 5\n", 4).is_err());
+        assert!(parse_cages("aab.\ncdb.\ncd..\n....\n\na 3\nb 7\nc 5\nd four\n", 4).is_err());
+    }
+}